@@ -153,17 +153,67 @@ pub trait ClassPath {
     fn class_path() -> String;
 }
 
-/// Trait used to construct the fixture. It is auto-implemented for fixtures that also implement Default.
-pub trait Constructor {
-    fn construct(args: Vec<String>) -> Self;
+/// Trait used to construct the fixture with the arguments of the `Make` instruction.
+/// The `#[fixture]` macro will automatically implement it for the type in the impl block.
+/// If the impl block has a public `new` function, each argument is parsed using the [FromStr](https://doc.rust-lang.org/std/str/trait.FromStr.html) trait and passed to it.
+/// Otherwise the fixture is built using [Default] and does not accept any argument.
+/// ```
+/// use rust_slim::fixture;
+/// struct Fixture {
+///     tenant: u64,
+/// }
+///
+/// #[fixture]
+/// impl Fixture {
+///     pub fn new(tenant: u64) -> Self {
+///         Self { tenant }
+///     }
+/// }
+/// ```
+///
+/// Versions up to 0.2.2 implemented it for every type implementing [Default], which left no room
+/// for arguments. A fixture implemented by hand that relied on it now implements
+/// [DefaultConstructor] instead.
+pub trait Constructor: Sized {
+    fn construct(args: Vec<String>) -> Result<Self, ExecuteMethodError>;
 }
 
-impl<T> Constructor for T
-where
-    T: Default,
-{
-    fn construct(_args: Vec<String>) -> T {
-        T::default()
+/// Builds a fixture implemented by hand using [Default], so it does not accept any argument.
+/// ```
+/// use rust_slim::{ClassPath, DefaultConstructor, ExecuteMethodError, SlimFixture};
+///
+/// #[derive(Default)]
+/// struct Fixture {}
+///
+/// impl SlimFixture for Fixture {
+///     fn execute_method(
+///         &mut self,
+///         method: &str,
+///         _args: Vec<String>,
+///     ) -> Result<String, ExecuteMethodError> {
+///         Err(ExecuteMethodError::MethodNotFound {
+///             method: method.to_string(),
+///             class: Self::class_path(),
+///         })
+///     }
+/// }
+///
+/// impl ClassPath for Fixture {
+///     fn class_path() -> String {
+///         "Fixtures.Fixture".into()
+///     }
+/// }
+///
+/// impl DefaultConstructor for Fixture {}
+/// ```
+pub trait DefaultConstructor: Default {}
+
+impl<T: DefaultConstructor> Constructor for T {
+    fn construct(args: Vec<String>) -> Result<Self, ExecuteMethodError> {
+        if !args.is_empty() {
+            return Err(ExecuteMethodError::ArgumentParsingError(args.join(",")));
+        }
+        Ok(T::default())
     }
 }
 
@@ -176,6 +226,10 @@ pub enum ExecuteMethodError {
     ArgumentParsingError(String),
     /// And there might be some failure in the method itself, which should cause an ExecutionError.
     ExecutionError(String),
+    /// The fixture can stop the test, so the remaining instructions and tables of the document are skipped, with the reason why.
+    StopTest(String),
+    /// Or stop the whole suite, so no other document is tested either.
+    StopSuite(String),
 }
 
 impl Display for ExecuteMethodError {
@@ -188,6 +242,12 @@ impl Display for ExecuteMethodError {
                 write!(f, "NO_CONVERTER_FOR_ARGUMENT_NUMBER {argument}")
             }
             ExecuteMethodError::ExecutionError(error) => f.write_str(error),
+            ExecuteMethodError::StopTest(reason) => {
+                write!(f, "ABORT_SLIM_TEST: message:<<{reason}>>")
+            }
+            ExecuteMethodError::StopSuite(reason) => {
+                write!(f, "ABORT_SLIM_SUITE: message:<<{reason}>>")
+            }
         }
     }
 }
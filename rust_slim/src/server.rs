@@ -1,4 +1,4 @@
-use crate::{ClassPath, Constructor, SlimFixture};
+use crate::{ClassPath, Constructor, ExecuteMethodError, SlimFixture};
 use convert_case::{Case, Casing};
 use slim_protocol::{
     ByeOrSlimInstructions, ExceptionMessage, FromSlimReader, FromSlimReaderError, Instruction,
@@ -19,13 +19,14 @@ pub enum SlimServerError {
     FromSlimReaderError(#[from] FromSlimReaderError),
 }
 
-pub type SlimClosureConstructor = Box<dyn Fn(Vec<String>) -> Box<dyn SlimFixture>>;
+pub type SlimClosureConstructor =
+    Box<dyn Fn(Vec<String>) -> Result<Box<dyn SlimFixture>, ExecuteMethodError>>;
 
 /// The SlimServer responsible to get the Slim commands and execute against the Fixtures.
 pub struct SlimServer<R: Read, W: Write> {
     fixtures: HashMap<String, SlimClosureConstructor>,
     instances: HashMap<String, Box<dyn SlimFixture>>,
-    libraries: HashMap<String, Box<dyn SlimFixture>>,
+    libraries: Vec<(String, Box<dyn SlimFixture>)>,
     symbols: HashMap<String, String>,
     imports: Vec<String>,
     reader: BufReader<R>,
@@ -38,7 +39,7 @@ impl<R: Read, W: Write> SlimServer<R, W> {
         Self {
             fixtures: HashMap::new(),
             instances: HashMap::new(),
-            libraries: HashMap::new(),
+            libraries: Vec::new(),
             symbols: HashMap::new(),
             reader: BufReader::new(reader),
             imports: Vec::new(),
@@ -50,8 +51,9 @@ impl<R: Read, W: Write> SlimServer<R, W> {
     pub fn add_fixture<T: ClassPath + Constructor + SlimFixture + 'static>(&mut self) {
         self.fixtures.insert(
             T::class_path(),
-            Box::new(|args: Vec<String>| Box::new(T::construct(args)) as Box<dyn SlimFixture>)
-                as Box<dyn Fn(Vec<String>) -> Box<dyn SlimFixture>>,
+            Box::new(|args: Vec<String>| {
+                T::construct(args).map(|fixture| Box::new(fixture) as Box<dyn SlimFixture>)
+            }) as SlimClosureConstructor,
         );
     }
 
@@ -71,8 +73,17 @@ impl<R: Read, W: Write> SlimServer<R, W> {
     }
 
     fn execute_instructions(&mut self, instructions: Vec<Instruction>) -> Vec<InstructionResult> {
-        let mut results = Vec::new();
+        let mut results: Vec<InstructionResult> = Vec::new();
         for instruction in instructions {
+            // Like other slim servers, nothing else is executed once a fixture stops the test.
+            if results.last().is_some_and(|result| match &result.value {
+                InstructionResultValue::Exception(message) => {
+                    message.is_stop_test() || message.is_stop_suite()
+                }
+                _ => false,
+            }) {
+                break;
+            }
             match instruction {
                 Instruction::Import { id, path } => {
                     self.imports.push(path);
@@ -93,10 +104,33 @@ impl<R: Read, W: Write> SlimServer<R, W> {
                         continue;
                     };
                     let args = self.parse_symbols(args);
+                    let fixture = match fixture(args) {
+                        Ok(fixture) => fixture,
+                        Err(
+                            error @ (ExecuteMethodError::StopTest(_)
+                            | ExecuteMethodError::StopSuite(_)),
+                        ) => {
+                            results.push(InstructionResult::exception(
+                                id,
+                                ExceptionMessage::new(error.to_string()),
+                            ));
+                            continue;
+                        }
+                        Err(error) => {
+                            results.push(InstructionResult::exception(
+                                id,
+                                ExceptionMessage::new(format!(
+                                    "COULD_NOT_INVOKE_CONSTRUCTOR {class} {error}"
+                                )),
+                            ));
+                            continue;
+                        }
+                    };
                     if instance.starts_with("library") {
-                        self.libraries.insert(instance, fixture(args));
+                        self.libraries.retain(|(name, _)| *name != instance);
+                        self.libraries.push((instance, fixture));
                     } else {
-                        self.instances.insert(instance, fixture(args));
+                        self.instances.insert(instance, fixture);
                     }
                     results.push(InstructionResult::ok(id))
                 }
@@ -107,21 +141,15 @@ impl<R: Read, W: Write> SlimServer<R, W> {
                     args,
                 } => {
                     let args = self.parse_symbols(args);
-                    let instances = if instance.starts_with("library") {
-                        &mut self.libraries
-                    } else {
-                        &mut self.instances
-                    };
-                    let Some(instance) = instances.get_mut(&instance) else {
+                    let Some(result) = self.execute_method(&instance, &function, args) else {
                         results.push(InstructionResult::exception(
                             id,
                             ExceptionMessage::new(format!("NO_INSTANCE: {instance}")),
                         ));
                         continue;
                     };
-                    let function = function.to_case(Case::Snake);
 
-                    match instance.execute_method(&function, args) {
+                    match result {
                         Ok(value) if value == "/__VOID__/" => {
                             results.push(InstructionResult::void(id))
                         }
@@ -152,21 +180,15 @@ impl<R: Read, W: Write> SlimServer<R, W> {
                     args,
                 } => {
                     let args = self.parse_symbols(args);
-                    let instances = if instance.starts_with("library") {
-                        &mut self.libraries
-                    } else {
-                        &mut self.instances
-                    };
-                    let Some(instance) = instances.get_mut(&instance) else {
+                    let Some(result) = self.execute_method(&instance, &function, args) else {
                         results.push(InstructionResult::exception(
                             id,
                             ExceptionMessage::new(format!("NO_INSTANCE: {instance}")),
                         ));
                         continue;
                     };
-                    let function = function.to_case(Case::Snake);
                     let symbol = symbol.strip_prefix('$').unwrap_or(&symbol).into();
-                    match instance.execute_method(&function, args) {
+                    match result {
                         Ok(value) if value == "/__VOID__/" => {
                             results.push(InstructionResult::void(id));
                             self.symbols.insert(symbol, "".into());
@@ -191,6 +213,38 @@ impl<R: Read, W: Write> SlimServer<R, W> {
         results
     }
 
+    /// Executes a method in the instance, returning `None` if the instance does not exist.
+    /// Methods not found in a regular instance are looked up in the libraries, the most recently created first.
+    fn execute_method(
+        &mut self,
+        instance: &str,
+        function: &str,
+        args: Vec<String>,
+    ) -> Option<Result<String, ExecuteMethodError>> {
+        let function = function.to_case(Case::Snake);
+        if instance.starts_with("library") {
+            let (_, library) = self
+                .libraries
+                .iter_mut()
+                .find(|(name, _)| name == instance)?;
+            return Some(library.execute_method(&function, args));
+        }
+        let result = self
+            .instances
+            .get_mut(instance)?
+            .execute_method(&function, args.clone());
+        if !matches!(result, Err(ExecuteMethodError::MethodNotFound { .. })) {
+            return Some(result);
+        }
+        for (_, library) in self.libraries.iter_mut().rev() {
+            match library.execute_method(&function, args.clone()) {
+                Err(ExecuteMethodError::MethodNotFound { .. }) => continue,
+                library_result => return Some(library_result),
+            }
+        }
+        Some(result)
+    }
+
     fn find_fixture(&self, class: &str) -> Option<&SlimClosureConstructor> {
         if let Some(fixture) = self.fixtures.get(class) {
             return Some(fixture);
@@ -299,7 +353,10 @@ mod tests {
         assert!(slim_server.instances.contains_key("Instance1"));
         assert!(slim_server.instances.contains_key("Instance2"));
         assert_eq!(1, slim_server.libraries.len());
-        assert!(slim_server.libraries.contains_key("libraryInstance"));
+        assert!(slim_server
+            .libraries
+            .iter()
+            .any(|(name, _)| name == "libraryInstance"));
         assert_eq!(
             vec![
                 InstructionResult::ok(Id::from("m_1")),
@@ -311,6 +368,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn execute_make_with_args() -> Result<(), Box<dyn Error>> {
+        let mut vec = Vec::new();
+        let reader = Cursor::new(&mut vec);
+        let mut vec = Vec::new();
+        let writer = Cursor::new(&mut vec);
+        let mut slim_server = SlimServer::new(reader, writer);
+        slim_server.add_fixture::<TestFixture>();
+        slim_server
+            .symbols
+            .insert("TENANT".into(), "tenant_1".into());
+        let result = slim_server.execute_instructions(vec![
+            Instruction::Make {
+                id: Id::from("m_1"),
+                instance: "Instance".into(),
+                class: "Test.TestFixture".into(),
+                args: vec!["$TENANT".into()],
+            },
+            Instruction::Make {
+                id: Id::from("m_2"),
+                instance: "Instance2".into(),
+                class: "Test.TestFixture".into(),
+                args: vec!["a".into(), "b".into()],
+            },
+            Instruction::Call {
+                id: Id::from("c_1"),
+                instance: "Instance".into(),
+                function: "value".into(),
+                args: Vec::new(),
+            },
+        ]);
+
+        assert!(!slim_server.instances.contains_key("Instance2"));
+        assert_eq!(
+            vec![
+                InstructionResult::ok(Id::from("m_1")),
+                InstructionResult::exception(
+                    Id::from("m_2"),
+                    ExceptionMessage::new(
+                        "COULD_NOT_INVOKE_CONSTRUCTOR Test.TestFixture NO_CONVERTER_FOR_ARGUMENT_NUMBER a,b"
+                            .into()
+                    )
+                ),
+                InstructionResult::string(Id::from("c_1"), "tenant_1".into()),
+            ],
+            result
+        );
+        Ok(())
+    }
+
     #[test]
     fn execute_call() -> Result<(), Box<dyn Error>> {
         let mut vec = Vec::new();
@@ -358,6 +465,133 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn stop_executing_instructions_when_the_test_is_stopped() -> Result<(), Box<dyn Error>> {
+        let mut vec = Vec::new();
+        let reader = Cursor::new(&mut vec);
+        let mut vec = Vec::new();
+        let writer = Cursor::new(&mut vec);
+        let mut slim_server = SlimServer::new(reader, writer);
+        add_test_fixture_with_path(
+            &mut slim_server,
+            "Test.StopFixture",
+            Err(ExecuteMethodError::StopTest("Database is down".into())),
+        );
+        let result = slim_server.execute_instructions(vec![
+            Instruction::Make {
+                id: Id::from("m_1"),
+                instance: "Instance".into(),
+                class: "Test.StopFixture".into(),
+                args: Vec::new(),
+            },
+            Instruction::Call {
+                id: Id::from("c_1"),
+                instance: "Instance".into(),
+                function: "method".into(),
+                args: Vec::new(),
+            },
+            Instruction::Call {
+                id: Id::from("c_2"),
+                instance: "Instance".into(),
+                function: "echo".into(),
+                args: vec!["Arg".into()],
+            },
+        ]);
+
+        assert_eq!(
+            vec![
+                InstructionResult::ok(Id::from("m_1")),
+                InstructionResult::exception(
+                    Id::from("c_1"),
+                    ExceptionMessage::new("ABORT_SLIM_TEST: message:<<Database is down>>".into())
+                ),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn execute_call_should_fallback_to_libraries() -> Result<(), Box<dyn Error>> {
+        let mut vec = Vec::new();
+        let reader = Cursor::new(&mut vec);
+        let mut vec = Vec::new();
+        let writer = Cursor::new(&mut vec);
+        let mut slim_server = SlimServer::new(reader, writer);
+        slim_server.add_fixture::<TestFixture>();
+        add_test_fixture_with_path(
+            &mut slim_server,
+            "Test.NoMethodFixture",
+            Err(ExecuteMethodError::MethodNotFound {
+                method: "method".into(),
+                class: "Test.NoMethodFixture".into(),
+            }),
+        );
+        let result = slim_server.execute_instructions(vec![
+            Instruction::Make {
+                id: Id::from("m_1"),
+                instance: "Instance".into(),
+                class: "Test.NoMethodFixture".into(),
+                args: Vec::new(),
+            },
+            Instruction::Call {
+                id: Id::from("c_1"),
+                instance: "Instance".into(),
+                function: "method".into(),
+                args: Vec::new(),
+            },
+            Instruction::Make {
+                id: Id::from("m_2"),
+                instance: "library1".into(),
+                class: "Test.TestFixture".into(),
+                args: vec!["First".into()],
+            },
+            Instruction::Make {
+                id: Id::from("m_3"),
+                instance: "library2".into(),
+                class: "Test.TestFixture".into(),
+                args: vec!["Second".into()],
+            },
+            Instruction::Call {
+                id: Id::from("c_2"),
+                instance: "Instance".into(),
+                function: "method".into(),
+                args: Vec::new(),
+            },
+            Instruction::Call {
+                id: Id::from("c_3"),
+                instance: "Instance".into(),
+                function: "echo".into(),
+                args: vec!["Arg".into()],
+            },
+            Instruction::CallAndAssign {
+                id: Id::from("c_4"),
+                symbol: "V".into(),
+                instance: "Instance".into(),
+                function: "method".into(),
+                args: Vec::new(),
+            },
+        ]);
+
+        assert_eq!(
+            vec![
+                InstructionResult::ok(Id::from("m_1")),
+                InstructionResult::exception(
+                    Id::from("c_1"),
+                    ExceptionMessage::new("NO_METHOD_IN_CLASS method Test.NoMethodFixture".into())
+                ),
+                InstructionResult::ok(Id::from("m_2")),
+                InstructionResult::ok(Id::from("m_3")),
+                InstructionResult::string(Id::from("c_2"), "Second".into()),
+                InstructionResult::string(Id::from("c_3"), "Arg".into()),
+                InstructionResult::string(Id::from("c_4"), "Second".into()),
+            ],
+            result
+        );
+        assert_eq!(Some(&"Second".to_string()), slim_server.symbols.get("V"));
+        Ok(())
+    }
+
     #[test]
     fn execute_call_and_assign() -> Result<(), Box<dyn Error>> {
         let mut vec = Vec::new();
@@ -549,9 +783,8 @@ mod tests {
         );
         slim_server.imports.push("Namespace".into());
 
-        let mut result = slim_server
-            .find_fixture("ExamplePathFixutre".into())
-            .unwrap()(Vec::new());
+        let mut result =
+            slim_server.find_fixture("ExamplePathFixutre").unwrap()(Vec::new()).unwrap();
         assert_eq!(
             Ok("First".to_string()),
             result.execute_method("", Vec::new())
@@ -578,15 +811,35 @@ mod tests {
         slim_server.imports.push("Namespace2".into());
         slim_server.imports.push("Namespace1".into());
 
-        let mut result = slim_server
-            .find_fixture("ExamplePathFixutre".into())
-            .unwrap()(Vec::new());
+        let mut result =
+            slim_server.find_fixture("ExamplePathFixutre").unwrap()(Vec::new()).unwrap();
         assert_eq!(
             Ok("Second".to_string()),
             result.execute_method("", Vec::new())
         );
     }
 
+    #[test]
+    fn construct_a_fixture_with_default() {
+        let mut vec = Vec::new();
+        let reader = Cursor::new(&mut vec);
+        let mut vec = Vec::new();
+        let writer = Cursor::new(&mut vec);
+        let mut slim_server = SlimServer::new(reader, writer);
+        slim_server.add_fixture::<DefaultFixture>();
+
+        let construct = slim_server.find_fixture("Test.DefaultFixture").unwrap();
+        let mut fixture = construct(Vec::new()).unwrap();
+        assert_eq!(
+            Ok("Default".to_string()),
+            fixture.execute_method("", Vec::new())
+        );
+        assert_eq!(
+            Some(ExecuteMethodError::ArgumentParsingError("1".into())),
+            construct(vec!["1".into()]).err()
+        );
+    }
+
     fn add_test_fixture_with_path<R: Read, W: Write>(
         server: &mut SlimServer<R, W>,
         class_path: impl Into<String>,
@@ -595,10 +848,10 @@ mod tests {
         server.fixtures.insert(
             class_path.into(),
             Box::new(move |_: Vec<String>| {
-                Box::new(TestFixture {
+                Ok(Box::new(TestFixture {
                     return_value: return_value.clone(),
-                }) as Box<dyn SlimFixture>
-            }) as Box<dyn Fn(Vec<String>) -> Box<dyn SlimFixture>>,
+                }) as Box<dyn SlimFixture>)
+            }) as SlimClosureConstructor,
         );
     }
 
@@ -626,10 +879,37 @@ mod tests {
     }
 
     impl Constructor for TestFixture {
-        fn construct(_: Vec<String>) -> Self {
-            Self {
-                return_value: Ok("Value".to_string()),
+        fn construct(args: Vec<String>) -> Result<Self, ExecuteMethodError> {
+            match args.as_slice() {
+                [] => Ok(Self {
+                    return_value: Ok("Value".to_string()),
+                }),
+                [value] => Ok(Self {
+                    return_value: Ok(value.clone()),
+                }),
+                _ => Err(ExecuteMethodError::ArgumentParsingError(args.join(","))),
             }
         }
     }
+
+    #[derive(Default)]
+    struct DefaultFixture;
+
+    impl SlimFixture for DefaultFixture {
+        fn execute_method(
+            &mut self,
+            _method: &str,
+            _parms: Vec<String>,
+        ) -> Result<String, crate::ExecuteMethodError> {
+            Ok("Default".into())
+        }
+    }
+
+    impl ClassPath for DefaultFixture {
+        fn class_path() -> String {
+            "Test.DefaultFixture".into()
+        }
+    }
+
+    impl crate::DefaultConstructor for DefaultFixture {}
 }
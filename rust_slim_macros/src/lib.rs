@@ -10,6 +10,7 @@ pub fn fixture(args: TokenStream, input: TokenStream) -> TokenStream {
 
     let class_path = get_class_path(args, ty);
     let impl_methods = impl_slim_fixture_methods(ty, &class_path, &impl_fixture.items);
+    let impl_constructor = impl_constructor(ty, &impl_fixture.items);
 
     quote! {
         #impl_fixture
@@ -23,6 +24,10 @@ pub fn fixture(args: TokenStream, input: TokenStream) -> TokenStream {
                 #class_path
             }
         }
+
+        impl #generics ::rust_slim::Constructor for #ty {
+            #impl_constructor
+        }
     }
     .into()
 }
@@ -57,7 +62,7 @@ fn impl_slim_fixture_methods(
                 quote!{args[#i].parse::<#ty>().map_err(|e| ::rust_slim::ExecuteMethodError::ArgumentParsingError(e.to_string()))?}
             });
             methods.push(quote! {
-                    #method => ::rust_slim::ToSlimResultString::to_slim_result_string(#ty::#ident(self,#(#args),*))
+                    #method => ::rust_slim::ToSlimResultString::to_slim_result_string(<#ty>::#ident(self,#(#args),*))
                 });
         }
     }
@@ -71,6 +76,47 @@ fn impl_slim_fixture_methods(
     }
 }
 
+/// A public `new` function builds the fixture from the arguments, while a `new` method with a
+/// receiver is just another method of the fixture.
+fn impl_constructor(ty: &Type, items: &[ImplItem]) -> proc_macro2::TokenStream {
+    let new_fn = items.iter().find_map(|item| match item {
+        ImplItem::Fn(impl_fn)
+            if impl_fn.sig.ident == "new"
+                && matches!(impl_fn.vis, Visibility::Public(_))
+                && impl_fn.sig.receiver().is_none() =>
+        {
+            Some(impl_fn)
+        }
+        _ => None,
+    });
+    let Some(new_fn) = new_fn else {
+        return quote! {
+            fn construct(args: ::std::vec::Vec<::std::string::String>) -> ::std::result::Result<Self, ::rust_slim::ExecuteMethodError> {
+                if !args.is_empty() {
+                    return Err(::rust_slim::ExecuteMethodError::ArgumentParsingError(args.join(",")));
+                }
+                Ok(<#ty as ::std::default::Default>::default())
+            }
+        };
+    };
+    let n_args = new_fn.sig.inputs.len();
+    let args = new_fn.sig.inputs.iter().enumerate().filter_map(|(i, fn_arg)| {
+        let FnArg::Typed(typed_arg) = fn_arg else {
+            return None;
+        };
+        let ty = &typed_arg.ty;
+        Some(quote!{args[#i].parse::<#ty>().map_err(|e| ::rust_slim::ExecuteMethodError::ArgumentParsingError(e.to_string()))?})
+    });
+    quote! {
+        fn construct(args: ::std::vec::Vec<::std::string::String>) -> ::std::result::Result<Self, ::rust_slim::ExecuteMethodError> {
+            if args.len() != #n_args {
+                return Err(::rust_slim::ExecuteMethodError::ArgumentParsingError(args.join(",")));
+            }
+            Ok(<#ty>::new(#(#args),*))
+        }
+    }
+}
+
 fn get_class_path(args: TokenStream, ty: &Type) -> proc_macro2::TokenStream {
     let args: Vec<String> = args
         .into_iter()
@@ -94,3 +140,52 @@ fn get_class_path(args: TokenStream, ty: &Type) -> proc_macro2::TokenStream {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn call_the_functions_of_a_type_with_generic_arguments() {
+        let impl_fixture: ItemImpl = parse_quote! {
+            impl Counter<u64> {
+                pub fn new(count: u64) -> Self {
+                    Self { count }
+                }
+
+                pub fn count(&self) -> u64 {
+                    self.count
+                }
+            }
+        };
+        let ty = &impl_fixture.self_ty;
+        let constructor = impl_constructor(ty, &impl_fixture.items).to_string();
+        assert!(
+            constructor.contains("< Counter < u64 > > :: new"),
+            "{constructor}"
+        );
+        let methods =
+            impl_slim_fixture_methods(ty, &quote!("Counter"), &impl_fixture.items).to_string();
+        assert!(
+            methods.contains("< Counter < u64 > > :: count"),
+            "{methods}"
+        );
+    }
+
+    #[test]
+    fn build_with_default_when_new_has_a_receiver() {
+        let impl_fixture: ItemImpl = parse_quote! {
+            impl Fixture {
+                pub fn new(&self) -> String {
+                    "new".into()
+                }
+            }
+        };
+        let constructor = impl_constructor(&impl_fixture.self_ty, &impl_fixture.items).to_string();
+        assert!(
+            constructor.contains("< Fixture as :: std :: default :: Default > :: default"),
+            "{constructor}"
+        );
+    }
+}
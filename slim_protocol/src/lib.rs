@@ -68,6 +68,12 @@ where
         Ok(())
     }
 
+    /// Drops the connection without saying goodbye, as when the server was killed and there is
+    /// no one left to hear it.
+    pub fn abandon(mut self) {
+        self.closed = true;
+    }
+
     fn say_goodbye(&mut self) -> Result<(), std::io::Error> {
         self.writer.write_all("bye".to_slim_string().as_bytes())?;
         self.writer.flush()?;
@@ -292,6 +298,29 @@ impl ExceptionMessage {
     pub fn is_no_method_in_class(&self) -> bool {
         self.0.contains("NO_METHOD_IN_CLASS")
     }
+
+    /// The class of the exception, when the message starts with one, like
+    /// `java.lang.IllegalArgumentException: message:<<Invalid amount>>`.
+    pub fn class_name(&self) -> Option<&str> {
+        let class_name = self.0.split(':').next()?.trim();
+        if class_name.is_empty()
+            || class_name == "message"
+            || class_name.contains(char::is_whitespace)
+        {
+            return None;
+        }
+        Some(class_name)
+    }
+
+    /// The fixture asked to stop the test, so the remaining tables of the document should not run.
+    pub fn is_stop_test(&self) -> bool {
+        self.0.starts_with("ABORT_SLIM_TEST")
+    }
+
+    /// The fixture asked to stop the whole suite, so no other document should run.
+    pub fn is_stop_suite(&self) -> bool {
+        self.0.starts_with("ABORT_SLIM_SUITE") || self.0.starts_with("STOP_SUITE")
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -368,4 +397,37 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_exception_message_classification() {
+        let stop_test =
+            ExceptionMessage::new("ABORT_SLIM_TEST: message:<<Database is down>>".into());
+        assert!(stop_test.is_stop_test());
+        assert!(!stop_test.is_stop_suite());
+        assert_eq!("Database is down", stop_test.pretty_message().unwrap());
+        let stop_suite = ExceptionMessage::new("ABORT_SLIM_SUITE: message:<<Out of disk>>".into());
+        assert!(stop_suite.is_stop_suite());
+        assert!(!stop_suite.is_stop_test());
+        assert!(ExceptionMessage::new("STOP_SUITE".into()).is_stop_suite());
+        let invalid = ExceptionMessage::new(
+            "java.lang.IllegalArgumentException: message:<<Invalid amount>>".into(),
+        );
+        assert_eq!(
+            Some("java.lang.IllegalArgumentException"),
+            invalid.class_name()
+        );
+        assert_eq!("Invalid amount", invalid.pretty_message().unwrap());
+        assert_eq!(
+            None,
+            ExceptionMessage::new("message:<<Invalid amount>>".into()).class_name()
+        );
+        assert_eq!(
+            None,
+            ExceptionMessage::new("Invalid amount".into()).class_name()
+        );
+        let other = ExceptionMessage::new("NO_METHOD_IN_CLASS sum Calculator".into());
+        assert!(other.is_no_method_in_class());
+        assert!(!other.is_stop_test());
+        assert!(!other.is_stop_suite());
+    }
 }
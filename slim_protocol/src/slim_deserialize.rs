@@ -60,23 +60,7 @@ where
 
 impl FromSlimReader for InstructionResultValue {
     fn from_reader(reader: &mut impl BufRead) -> Result<Self, FromSlimReaderError> {
-        let value = String::from_reader(reader)?;
-        Ok(match value.as_str() {
-            "OK" => InstructionResultValue::Ok,
-            "/__VOID__/" => InstructionResultValue::Void,
-            value if value.starts_with('[') => {
-                let values: Vec<InstructionResultValue> =
-                    Vec::from_reader(&mut Cursor::new(value))?;
-                InstructionResultValue::List(values)
-            }
-            other => {
-                if let Some(message) = other.strip_prefix("__EXCEPTION__:") {
-                    InstructionResultValue::Exception(ExceptionMessage::new(message.into()))
-                } else {
-                    InstructionResultValue::String(other.into())
-                }
-            }
-        })
+        instruction_result_value_from_string(String::from_reader(reader)?)
     }
 }
 
@@ -86,27 +70,33 @@ impl FromSlimReader for InstructionResult {
         let id = Id::from(id);
         Ok(InstructionResult {
             id,
-            value: match value.as_str() {
-                "OK" => InstructionResultValue::Ok,
-                "/__VOID__/" => InstructionResultValue::Void,
-                value if value.starts_with('[') => {
-                    let value = format!("{:0>6}:{}", value.len(), value);
-                    let values: Vec<InstructionResultValue> =
-                        Vec::from_reader(&mut Cursor::new(value))?;
-                    InstructionResultValue::List(values)
-                }
-                other => {
-                    if let Some(message) = other.strip_prefix("__EXCEPTION__:") {
-                        InstructionResultValue::Exception(ExceptionMessage::new(message.into()))
-                    } else {
-                        InstructionResultValue::String(other.into())
-                    }
-                }
-            },
+            value: instruction_result_value_from_string(value)?,
         })
     }
 }
 
+fn instruction_result_value_from_string(
+    value: String,
+) -> Result<InstructionResultValue, FromSlimReaderError> {
+    Ok(match value.as_str() {
+        "OK" => InstructionResultValue::Ok,
+        "/__VOID__/" => InstructionResultValue::Void,
+        value if value.starts_with('[') => {
+            // Lists are sent as strings, so the length prefix that Vec expects is missing.
+            let value = format!("{:0>6}:{}", value.len(), value);
+            let values: Vec<InstructionResultValue> = Vec::from_reader(&mut Cursor::new(value))?;
+            InstructionResultValue::List(values)
+        }
+        other => {
+            if let Some(message) = other.strip_prefix("__EXCEPTION__:") {
+                InstructionResultValue::Exception(ExceptionMessage::new(message.into()))
+            } else {
+                InstructionResultValue::String(other.into())
+            }
+        }
+    })
+}
+
 impl FromSlimReader for Instruction {
     fn from_reader(reader: &mut impl BufRead) -> Result<Self, FromSlimReaderError>
     where
@@ -402,6 +392,21 @@ mod test {
                 "000073:[000002:000026:01HFM0NQM3ZS6BBX0ZH6VA6DJX:0000021:__EXCEPTION__:Message:]"
             ))?
         );
+        assert_eq!(
+            InstructionResult {
+                id: id.clone(),
+                value: InstructionResultValue::List(vec![
+                    InstructionResultValue::List(vec![
+                        InstructionResultValue::String("name".to_string()),
+                        InstructionResultValue::String("bob".to_string())
+                    ]),
+                    InstructionResultValue::List(vec![])
+                ]),
+            },
+            InstructionResult::from_reader(&mut Cursor::new(
+                "000117:[000002:000026:01HFM0NQM3ZS6BBX0ZH6VA6DJX:000066:[000002:000032:[000002:000004:name:000003:bob:]:000009:[000000:]:]:]"
+            ))?
+        );
         let exception = InstructionResult::from_reader(&mut Cursor::new(
             "000100:[000002:000026:01HFM0NQM3ZS6BBX0ZH6VA6DJX:0000048:__EXCEPTION__:Some Exception message:<<Message>>:]"
         ))?;
@@ -15,7 +15,7 @@ pub trait ToSlimString {
     fn to_slim_string(&self) -> SlimString;
 }
 
-impl<'a> ToSlimString for &'a str {
+impl ToSlimString for &str {
     fn to_slim_string(&self) -> SlimString {
         SlimString(format!("{:0>6}:{}", self.len(), self))
     }
@@ -36,7 +36,7 @@ where
     }
 }
 
-impl<'a, T> ToSlimString for &'a [T]
+impl<T> ToSlimString for &[T]
 where
     T: ToSlimString,
 {
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use fixtures::CalculatorFixture;
+use fixtures::{CalculatorFixture, MathLibraryFixture};
 use rust_slim::SlimServer;
 use std::env::args;
 use std::io::{stdin, stdout, Read, Write};
@@ -40,17 +40,28 @@ mod fixtures {
             self.a / self.b
         }
 
-        pub fn log(&self, a: f64, b: f64) -> [String; 2] {
-            [format!("{:.2}", a.log(b)), format!("{:.2}", b.log(a))]
+        pub fn log(&self, a: f64, b: f64) -> [f64; 2] {
+            [a.log(b), b.log(a)]
+        }
+    }
+
+    #[derive(Default)]
+    pub struct MathLibraryFixture;
+
+    #[fixture]
+    impl MathLibraryFixture {
+        pub fn double(&self, value: i64) -> i64 {
+            value * 2
         }
     }
 }
 
 fn main() -> Result<()> {
-    let port = args().skip(1).next().unwrap_or("8085".to_string());
+    let port = args().nth(1).unwrap_or("8085".to_string());
     let mut server = build_server(&port)?;
 
     server.add_fixture::<CalculatorFixture>();
+    server.add_fixture::<MathLibraryFixture>();
     server.run()?;
     Ok(())
 }
@@ -1,71 +1,531 @@
+use crate::output::{Format, Output};
 use crate::processor::{
-    execute_instructions_and_print_result, process_markdown_into_instructions, Filter, State,
+    assign_symbols, execute_test_case, markdown_into_instructions, reset_fixture,
+    skipped_test_case, test_case_batches, timed_out_test_case, Abort, Filter, MarkdownInstructions,
+    State, TestCaseResult,
 };
-use crate::slim_server_connector::SlimServerConnector;
-use anyhow::Result;
+use crate::report::{Report, TestSuiteResult};
+use crate::slim_server_connector::{KillSwitch, SlimServer, SlimServerConnector};
+use crate::update::{update_markdown, Update};
+use crate::watch::{Changes, Watch};
+use crate::watchdog::{Cancelled, Timeout, Timeouts, Watchdog};
+use anyhow::{anyhow, Error, Result};
 use slim_protocol::SlimConnection;
 use std::fs::{metadata, read_dir};
+use std::io::{stdout, Read, Write};
+use std::mem::take;
 use std::num::ParseIntError;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{scope, sleep};
+use std::time::{Duration, Instant};
+
+const SET_UP: &str = "SetUp";
+const TEAR_DOWN: &str = "TearDown";
+const SUITE_SET_UP: &str = "SuiteSetUp";
+const SUITE_TEAR_DOWN: &str = "SuiteTearDown";
+/// How often the watched paths are checked for changes.
+const WATCH_POLL: Duration = Duration::from_millis(500);
 
 pub struct App {
-    show_snoozed: bool,
+    output: Output,
     slim_server_connector: Box<dyn SlimServerConnector>,
     recursive: bool,
     extension: String,
     filter: Filter,
     paths: Vec<PathBuf>,
+    /// The directory argument being processed, where the lookup of set ups and tear downs stops.
+    root: Option<PathBuf>,
+    /// Symbols set by the suite set ups of the directories being processed.
+    suite_state: State,
+    reports: Vec<Report>,
+    test_suites: Vec<TestSuiteResult>,
+    update: Option<Update>,
+    /// How many files are tested concurrently.
+    jobs: usize,
+    /// Keep the slim server of each job alive across the files it tests.
+    reuse_server: bool,
+    /// Fixture made at the start of each file to clear the state left in a reused server.
+    reset_fixture: Option<String>,
+    timeouts: Timeouts,
+    /// Stop testing a file at its first failing table.
+    stop_on_error: bool,
+    /// Why a fixture stopped the suite, skipping every file tested after it.
+    suite_stopped: OnceLock<String>,
+    /// Set when watching to cancel the tests, killing their slim servers, once a new change arrives.
+    cancelled: Option<Arc<AtomicBool>>,
+    /// The session every file and suite page share when the slim server accepts a single
+    /// connection, kept between the tests while watching.
+    shared_session: Mutex<Option<SlimSession>>,
+}
+
+/// A slim server with its connection, that can be kept alive across files.
+struct SlimSession {
+    server: Box<dyn SlimServer>,
+    connection: SlimConnection<Box<dyn Read + Send>, Box<dyn Write + Send>>,
+    kill_switch: KillSwitch,
+}
+
+impl SlimSession {
+    fn start(slim_server_connector: &dyn SlimServerConnector) -> Result<Self> {
+        let mut server = slim_server_connector.start_and_connect()?;
+        let connection = SlimConnection::new(server.reader()?, server.writer()?)?;
+        let kill_switch = server.kill_switch()?;
+        Ok(Self {
+            server,
+            connection,
+            kill_switch,
+        })
+    }
+
+    fn close(mut self) -> Result<()> {
+        self.connection.close()?;
+        self.server.close()
+    }
+
+    /// Once killed, there is no one left to say goodbye to.
+    fn close_killed(mut self) -> Result<()> {
+        self.connection.abandon();
+        self.server.close()
+    }
 }
 
 impl App {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         show_snoozed: bool,
+        format: Format,
         slim_server_connector: Box<dyn SlimServerConnector>,
         recursive: bool,
         filter: Filter,
         extension: String,
         paths: Vec<PathBuf>,
+        reports: Vec<Report>,
+        update: Option<Update>,
+        jobs: usize,
+        reuse_server: bool,
+        reset_fixture: Option<String>,
+        timeouts: Timeouts,
+        stop_on_error: bool,
     ) -> Self {
         App {
-            show_snoozed,
+            output: Output::new(format, show_snoozed),
             slim_server_connector,
             recursive,
             extension,
             filter,
             paths,
+            root: None,
+            suite_state: State::default(),
+            reports,
+            test_suites: Vec::new(),
+            update,
+            jobs,
+            reuse_server,
+            reset_fixture,
+            timeouts,
+            stop_on_error,
+            suite_stopped: OnceLock::new(),
+            cancelled: None,
+            shared_session: Mutex::new(None),
         }
     }
 
     pub fn run(mut self) -> Result<bool> {
-        self.process_paths(self.paths.clone())
+        let fail = self.test(self.paths.clone(), &mut stdout())?;
+        self.close_shared_session()?;
+        Ok(fail)
+    }
+
+    /// Tests the paths, then keeps testing again the files that change until interrupted. A change
+    /// to the fixture sources, or to a support page, tests all the paths again.
+    pub fn watch(mut self, sources: Vec<PathBuf>) -> Result<()> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.cancelled = Some(cancelled.clone());
+        // The slim servers are in their own process group, so they are killed here instead of by
+        // the terminal.
+        let interrupted = Arc::new(AtomicBool::new(false));
+        {
+            let (interrupted, cancelled) = (interrupted.clone(), cancelled.clone());
+            ctrlc::set_handler(move || {
+                interrupted.store(true, Ordering::SeqCst);
+                cancelled.store(true, Ordering::SeqCst);
+            })?;
+        }
+        let test_paths = self
+            .paths
+            .iter()
+            .map(|path| self.split_line_filter(path.clone()).0)
+            .collect();
+        let mut watch = Watch::new(test_paths, self.recursive, sources);
+        let mut paths = self.paths.clone();
+        loop {
+            let was_cancelled = self.test_while_watching(paths.clone(), &mut watch, &cancelled)?;
+            if was_cancelled {
+                self.output.cancelled(&mut stdout())?;
+            }
+            if interrupted.load(Ordering::SeqCst) {
+                return self.close_shared_session();
+            }
+            let mut affected = if was_cancelled {
+                self.unfinished_files(paths)?
+            } else {
+                self.output.watching(&mut stdout())?;
+                vec![]
+            };
+            loop {
+                // Waits for the changes to settle, so a save that writes several times runs once.
+                while watch.poll() {
+                    sleep(WATCH_POLL);
+                }
+                if interrupted.load(Ordering::SeqCst) {
+                    return self.close_shared_session();
+                }
+                let changes = watch.take();
+                for path in self.affected_paths(&changes) {
+                    if !affected.contains(&path) {
+                        affected.push(path);
+                    }
+                }
+                if !affected.is_empty() {
+                    self.output.changes_detected(&mut stdout(), &changes)?;
+                    break;
+                }
+                sleep(WATCH_POLL);
+            }
+            paths = affected;
+        }
+    }
+
+    /// Tests the paths, cancelling them as soon as a change arrives. Returns if they were
+    /// cancelled.
+    fn test_while_watching(
+        &mut self,
+        paths: Vec<PathBuf>,
+        watch: &mut Watch,
+        cancelled: &AtomicBool,
+    ) -> Result<bool> {
+        cancelled.store(false, Ordering::SeqCst);
+        let result = scope(|scope| {
+            let testing = scope.spawn(|| self.test(paths, &mut stdout()));
+            while !testing.is_finished() {
+                sleep(WATCH_POLL);
+                if watch.poll() {
+                    cancelled.store(true, Ordering::SeqCst);
+                }
+            }
+            testing
+                .join()
+                .map_err(|_| anyhow!("Testing the files panicked"))?
+        });
+        match result {
+            Err(err) if err.is::<Cancelled>() => Ok(true),
+            // Like a table that can't be parsed, it may be fixed by the next change.
+            Err(err) => {
+                self.output.error(&mut stdout(), &err)?;
+                Ok(false)
+            }
+            Ok(_) => Ok(false),
+        }
+    }
+
+    /// The paths to test again after the changes, keeping their line filter. The fixture sources
+    /// and the support pages can affect any file, so all the paths are tested again when they
+    /// change.
+    fn affected_paths(&self, changes: &Changes) -> Vec<PathBuf> {
+        if !changes.sources.is_empty()
+            || changes.tests.iter().any(|file| self.is_support_page(file))
+        {
+            return self.paths.clone();
+        }
+        changes
+            .tests
+            .iter()
+            .filter(|file| file.is_file() && self.is_correct_extension(file))
+            .map(|file| {
+                self.paths
+                    .iter()
+                    .find(|path| self.split_line_filter((*path).clone()).0 == *file)
+                    .unwrap_or(file)
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// The test files of the paths left without results by a cancel, like the ones not tested yet
+    /// and the ones being tested, keeping their line filter.
+    fn unfinished_files(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        let mut unfinished = Vec::new();
+        for path in paths {
+            let (file, _) = self.split_line_filter(path.clone());
+            if file.is_dir() {
+                if self.recursive {
+                    unfinished.extend(self.unfinished_files(get_list_of_files(&file)?)?);
+                }
+            } else if file.is_file()
+                && self.is_correct_extension(&file)
+                && !self.is_support_page(&file)
+                && !self
+                    .test_suites
+                    .iter()
+                    .any(|test_suite| test_suite.name == file.display().to_string())
+            {
+                unfinished.push(path);
+            }
+        }
+        Ok(unfinished)
+    }
+
+    /// Starts a session, or takes the shared one when the slim server accepts a single connection.
+    fn take_session(&mut self) -> Result<SlimSession> {
+        match self.shared_session().take() {
+            Some(session) => Ok(session),
+            None => SlimSession::start(self.slim_server_connector.as_ref()),
+        }
+    }
+
+    /// Keeps the session to share it when the slim server accepts a single connection, and closes
+    /// it otherwise.
+    fn give_back_session(&mut self, session: SlimSession, killed: bool) -> Result<()> {
+        if killed {
+            session.close_killed()
+        } else if self.slim_server_connector.single_connection() {
+            *self.shared_session() = Some(session);
+            Ok(())
+        } else {
+            session.close()
+        }
+    }
+
+    /// Only reached through `&mut self`, so it is never locked by anything else.
+    fn shared_session(&mut self) -> &mut Option<SlimSession> {
+        self.shared_session
+            .get_mut()
+            .expect("Nothing panics while holding the shared session")
+    }
+
+    fn close_shared_session(&mut self) -> Result<()> {
+        self.shared_session()
+            .take()
+            .map(SlimSession::close)
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Tests the paths, then prints the summary and writes the reports of their results.
+    fn test(&mut self, paths: Vec<PathBuf>, out: &mut impl Write) -> Result<bool> {
+        self.test_suites.clear();
+        self.suite_stopped = OnceLock::new();
+        let fail = self.process_paths(paths, out)?;
+        self.output.summary(out, &self.test_suites)?;
+        for report in &self.reports {
+            report.write(&self.test_suites)?;
+        }
+        Ok(fail)
     }
 
-    pub fn process_paths(&mut self, paths: Vec<PathBuf>) -> Result<bool> {
+    /// Directories are processed one after the other, while the files between them are tested by
+    /// up to `jobs` at the same time.
+    pub fn process_paths(&mut self, paths: Vec<PathBuf>, out: &mut impl Write) -> Result<bool> {
         let mut fail = false;
+        let mut files = Vec::new();
         for path in paths {
-            fail |= self.process_path(path)?;
+            let (path, filter) = self.split_line_filter(path);
+            let metadata = metadata(&path)?;
+            if metadata.is_dir() && self.recursive {
+                fail |= self.process_files(take(&mut files), out)?;
+                let root = self.root.clone();
+                self.root.get_or_insert_with(|| path.clone());
+                let result = self.process_dir(&path, out);
+                self.root = root;
+                fail |= result?;
+            } else if metadata.is_file()
+                && self.is_correct_extension(&path)
+                && !self.is_support_page(&path)
+            {
+                files.push((path, filter));
+            }
         }
+        fail |= self.process_files(files, out)?;
         Ok(fail)
     }
 
-    pub fn process_path(&mut self, path: impl AsRef<Path>) -> Result<bool> {
-        let mut path = path.as_ref().to_path_buf();
-        let mut filter = self.filter.clone();
+    /// A path ending with `:line` only tests the table at that line.
+    fn split_line_filter(&self, path: PathBuf) -> (PathBuf, Filter) {
+        let filter = self.filter.clone();
         if let Ok(line) = Self::get_line_from_path(&path) {
             if let Some(remaining) = path.to_string_lossy().strip_suffix(&format!(":{line}")) {
-                path = PathBuf::from(remaining);
-                filter = filter.line(line);
+                return (PathBuf::from(remaining), filter.line(line));
             }
         }
-        let metadata = metadata(&path)?;
-        if metadata.is_dir() && self.recursive {
-            return self.process_paths(get_list_of_files(&path)?);
+        (path, filter)
+    }
+
+    /// Tests the files, each of them with its own slim server unless reusing them. When running
+    /// more than one job the output of each file is buffered and printed once it finishes, so they
+    /// don't interleave.
+    fn process_files(
+        &mut self,
+        files: Vec<(PathBuf, Filter)>,
+        out: &mut impl Write,
+    ) -> Result<bool> {
+        let mut fail = false;
+        if self.jobs <= 1 || files.len() <= 1 || self.slim_server_connector.single_connection() {
+            let mut session = self.shared_session().take();
+            for (file, filter) in files {
+                let (file_fail, test_suite) =
+                    self.process_file(&file, filter, out, &mut session)?;
+                fail |= file_fail;
+                self.test_suites.push(test_suite);
+            }
+            if let Some(session) = session {
+                self.give_back_session(session, false)?;
+            }
+            return Ok(fail);
         }
-        if metadata.is_file() && self.is_correct_extension(&path) {
-            return self.process_file(&path, filter);
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = channel();
+        let app = &*self;
+        let mut finished = scope(|scope| {
+            let jobs: Vec<_> = (0..app.jobs.min(files.len()))
+                .map(|_| {
+                    let sender = sender.clone();
+                    let (next, files) = (&next, &files);
+                    scope.spawn(move || {
+                        let mut session = None;
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let Some((file, filter)) = files.get(index) else {
+                                break;
+                            };
+                            let mut buffer = Vec::new();
+                            let result =
+                                app.process_file(file, filter.clone(), &mut buffer, &mut session);
+                            if sender.send((index, result, buffer)).is_err() {
+                                break;
+                            }
+                        }
+                        session.map(SlimSession::close).transpose()
+                    })
+                })
+                .collect();
+            drop(sender);
+            let finished = receiver
+                .iter()
+                .map(|(index, result, buffer)| {
+                    out.write_all(&buffer)?;
+                    out.flush()?;
+                    Ok((index, result))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for job in jobs {
+                job.join()
+                    .map_err(|_| anyhow!("A job testing the files panicked"))??;
+            }
+            Ok::<_, Error>(finished)
+        })?;
+        // The results are kept in the order of the files, whichever finished first.
+        finished.sort_by_key(|(index, _)| *index);
+        for (_, result) in finished {
+            let (file_fail, test_suite) = result?;
+            fail |= file_fail;
+            self.test_suites.push(test_suite);
         }
-        Ok(false)
+        Ok(fail)
+    }
+
+    /// Processes the files of the directory and its subdirectories, wrapped by the suite set up and
+    /// tear down of the directory. They run once on their own slim server, or on the shared one
+    /// that the files of the directory are then tested on too.
+    fn process_dir(&mut self, dir: &Path, out: &mut impl Write) -> Result<bool> {
+        let paths = get_list_of_files(dir)?;
+        let suite_set_up = Some(dir.join(self.page_file_name(SUITE_SET_UP)))
+            .filter(|suite_set_up| suite_set_up.is_file());
+        let suite_tear_down = Some(dir.join(self.page_file_name(SUITE_TEAR_DOWN)))
+            .filter(|suite_tear_down| suite_tear_down.is_file());
+        if suite_set_up.is_none() && suite_tear_down.is_none() {
+            return self.process_paths(paths, out);
+        }
+        let mut session = self.take_session()?;
+        let previous_suite_state = self.suite_state.clone();
+        let mut state = self.suite_state.clone();
+        assign_symbols(&mut session.connection, &state)?;
+        let mut fail = false;
+        let mut killed = false;
+        if let Some(suite_set_up) = suite_set_up {
+            let start = Instant::now();
+            self.output.file_started(out, &suite_set_up, true)?;
+            let watchdog =
+                Watchdog::new(self.timeouts, &session.kill_switch, self.cancelled.as_ref());
+            let results = self
+                .execution(&mut session.connection, &watchdog, out)
+                .suite_page()
+                .execute_page(&suite_set_up, &mut state, false)?;
+            killed = watchdog.killed();
+            fail |= self.report_support_page(out, &suite_set_up, results, start)?;
+        }
+        self.suite_state = state.clone();
+        let (result, session) = if self.slim_server_connector.single_connection() && !killed {
+            *self.shared_session() = Some(session);
+            let result = self.process_paths(paths, out);
+            (result, self.shared_session().take())
+        } else {
+            (self.process_paths(paths, out), Some(session))
+        };
+        self.suite_state = previous_suite_state;
+        let Some(mut session) = session else {
+            // A file lost the shared session, leaving no server to run the suite tear down on.
+            return Ok(fail | result?);
+        };
+        // The suite tear down runs even if processing the directory failed, as long as the server
+        // is still alive.
+        if let Some(suite_tear_down) = suite_tear_down.filter(|_| !killed) {
+            let start = Instant::now();
+            self.output.file_started(out, &suite_tear_down, true)?;
+            let watchdog =
+                Watchdog::new(self.timeouts, &session.kill_switch, self.cancelled.as_ref());
+            let results = self
+                .execution(&mut session.connection, &watchdog, out)
+                .suite_page()
+                .execute_page(&suite_tear_down, &mut state, true)?;
+            killed = watchdog.killed();
+            fail |= self.report_support_page(out, &suite_tear_down, results, start)?;
+        }
+        fail |= result?;
+        self.give_back_session(session, killed)?;
+        Ok(fail)
+    }
+
+    /// Adds the results of a suite set up or tear down to the reports and prints them.
+    fn report_support_page(
+        &mut self,
+        out: &mut impl Write,
+        page: &Path,
+        results: Vec<TestCaseResult>,
+        start: Instant,
+    ) -> Result<bool> {
+        let (fail, test_suite) = self.finish_test_suite(out, page, results, start, true)?;
+        self.test_suites.push(test_suite);
+        Ok(fail)
+    }
+
+    fn finish_test_suite(
+        &self,
+        out: &mut impl Write,
+        file: &Path,
+        test_cases: Vec<TestCaseResult>,
+        start: Instant,
+        support_page: bool,
+    ) -> Result<(bool, TestSuiteResult)> {
+        let test_suite = TestSuiteResult {
+            name: file.display().to_string(),
+            test_cases,
+            time: start.elapsed(),
+        };
+        let fail = self.output.file_finished(out, &test_suite, support_page)?;
+        Ok((fail, test_suite))
     }
 
     fn get_line_from_path(path: &impl AsRef<Path>) -> Result<usize, ParseIntError> {
@@ -90,26 +550,257 @@ impl App {
             .ends_with(&self.extension)
     }
 
-    fn process_file(&mut self, file: impl AsRef<Path>, filter: Filter) -> Result<bool> {
-        let (instructions, expected_result) = process_markdown_into_instructions(&file, &filter)?;
-        if instructions.is_empty() {
-            println!("NONE");
-            return Ok(false);
-        }
-        let mut slim_server = self.slim_server_connector.start_and_connect()?;
-        let mut state = State::default();
-        let mut connection = SlimConnection::new(slim_server.reader()?, slim_server.writer()?)?;
-        let fail = execute_instructions_and_print_result(
-            &mut connection,
-            &file.as_ref().to_string_lossy(),
-            instructions,
-            expected_result,
-            self.show_snoozed,
-            &mut state,
-        )?;
-        connection.close()?;
-        slim_server.close()?;
-        Ok(fail)
+    fn is_support_page(&self, path: impl AsRef<Path>) -> bool {
+        path.as_ref()
+            .file_name()
+            .map(|file_name| {
+                [SET_UP, TEAR_DOWN, SUITE_SET_UP, SUITE_TEAR_DOWN]
+                    .iter()
+                    .any(|page| file_name.to_string_lossy() == self.page_file_name(page))
+            })
+            .unwrap_or(false)
+    }
+
+    fn page_file_name(&self, page: &str) -> String {
+        format!("{page}.{}", self.extension)
+    }
+
+    /// Finds the page in the directory of the file or in the closest ancestor that has it, up to
+    /// the directory argument being processed. A file given on its own only looks in its directory.
+    fn find_page(&self, file: &Path, page: &str) -> Option<PathBuf> {
+        let dir = file.parent()?;
+        let root = self
+            .root
+            .as_deref()
+            .filter(|root| dir.starts_with(root))
+            .unwrap_or(dir);
+        dir.ancestors()
+            .take(dir.components().count() - root.components().count() + 1)
+            .map(|dir| dir.join(self.page_file_name(page)))
+            .find(|page| page.is_file())
+    }
+
+    /// Tests the file on the session, starting one if there is none. The session is handed back
+    /// when reusing the server, and closed otherwise.
+    fn process_file(
+        &self,
+        file: &Path,
+        filter: Filter,
+        out: &mut impl Write,
+        reused_session: &mut Option<SlimSession>,
+    ) -> Result<(bool, TestSuiteResult)> {
+        let start = Instant::now();
+        self.output.file_started(out, file, false)?;
+        let markdown_instructions = markdown_into_instructions(file, &filter)?;
+        if markdown_instructions.0.is_empty() {
+            return self.finish_test_suite(out, file, vec![], start, false);
+        }
+        if let Some(reason) = self.suite_stopped.get() {
+            let file_path = file.to_string_lossy();
+            let results = markdown_instructions
+                .2
+                .iter()
+                .map(|test_case| {
+                    let result = skipped_test_case(&file_path, test_case, &suite_stopped(reason));
+                    self.output.test_case_finished(out, &result, false)?;
+                    Ok(result)
+                })
+                .collect::<Result<_>>()?;
+            return self.finish_test_suite(out, file, results, start, false);
+        }
+        let mut session = match reused_session.take() {
+            Some(session) => session,
+            None => SlimSession::start(self.slim_server_connector.as_ref())?,
+        };
+        let watchdog = Watchdog::new(self.timeouts, &session.kill_switch, self.cancelled.as_ref());
+        let result = self.execute_file(
+            &mut self.execution(&mut session.connection, &watchdog, out),
+            file,
+            markdown_instructions,
+        );
+        // A server that failed is not reused, as it may be left in the middle of a conversation.
+        match &result {
+            _ if watchdog.killed() => session.close_killed()?,
+            Ok(_) if self.reuse_server || self.slim_server_connector.single_connection() => {
+                *reused_session = Some(session)
+            }
+            _ => session.close()?,
+        }
+        let results = result?;
+        let updated_cells = match self.update {
+            Some(update) => update_markdown(file, &results, update)?,
+            None => 0,
+        };
+        let finished = self.finish_test_suite(out, file, results, start, false)?;
+        if updated_cells > 0 {
+            self.output.file_updated(out, file, updated_cells)?;
+        }
+        Ok(finished)
+    }
+
+    /// Executes the file wrapped by its set up and tear down, starting from the state of the suite.
+    fn execute_file<R: Read, W: Write, O: Write>(
+        &self,
+        execution: &mut Execution<R, W, O>,
+        file: &Path,
+        markdown_instructions: MarkdownInstructions,
+    ) -> Result<Vec<TestCaseResult>> {
+        if let Some(class) = &self.reset_fixture {
+            execution
+                .watchdog
+                .run(|| reset_fixture(execution.connection, class))?;
+        }
+        let set_up = self.find_page(file, SET_UP);
+        let tear_down = self.find_page(file, TEAR_DOWN);
+        let mut state = self.suite_state.clone();
+        execution
+            .watchdog
+            .run(|| assign_symbols(execution.connection, &state))?;
+        let result = set_up
+            .map(|set_up| execution.execute_page(&set_up, &mut state, false))
+            .unwrap_or(Ok(vec![]))
+            .and_then(|mut results| {
+                results.extend(execution.execute(
+                    &file.to_string_lossy(),
+                    markdown_instructions,
+                    &mut state,
+                    false,
+                )?);
+                Ok(results)
+            });
+        // The tear down runs even if the set up or the test itself failed.
+        let tear_down_result = tear_down
+            .map(|tear_down| execution.execute_page(&tear_down, &mut state, true))
+            .unwrap_or(Ok(vec![]));
+        let mut results = result?;
+        results.extend(tear_down_result?);
+        Ok(results)
+    }
+
+    fn execution<'a, R: Read, W: Write, O: Write>(
+        &'a self,
+        connection: &'a mut SlimConnection<R, W>,
+        watchdog: &'a Watchdog<'a>,
+        out: &'a mut O,
+    ) -> Execution<'a, R, W, O> {
+        Execution {
+            connection,
+            watchdog,
+            output: &self.output,
+            out,
+            stop_on_error: self.stop_on_error,
+            stopped: false,
+            suite_stopped: &self.suite_stopped,
+            skip_reason: self.suite_stopped.get().map(|reason| suite_stopped(reason)),
+            support_page: false,
+        }
+    }
+}
+
+fn suite_stopped(reason: &str) -> String {
+    format!("The suite was stopped: {reason}")
+}
+
+/// Executes the tables of a file and its support pages one by one on the same slim server,
+/// printing their results as they arrive.
+struct Execution<'a, R: Read, W: Write, O: Write> {
+    connection: &'a mut SlimConnection<R, W>,
+    watchdog: &'a Watchdog<'a>,
+    output: &'a Output,
+    out: &'a mut O,
+    /// Skip the remaining tables, except the ones of the tear down, once one fails.
+    stop_on_error: bool,
+    stopped: bool,
+    suite_stopped: &'a OnceLock<String>,
+    /// Why the remaining tables, except the ones of the tear down, are skipped once a fixture
+    /// stops the test or the suite.
+    skip_reason: Option<String>,
+    /// Executing a suite set up or tear down, whose tables are not printed as text.
+    support_page: bool,
+}
+
+impl<R: Read, W: Write, O: Write> Execution<'_, R, W, O> {
+    /// Executes a suite set up or tear down, whose tables are only printed as events.
+    fn suite_page(mut self) -> Self {
+        self.support_page = true;
+        self
+    }
+
+    /// Executes a support page, like a set up or a tear down.
+    fn execute_page(
+        &mut self,
+        page: &Path,
+        state: &mut State,
+        tear_down: bool,
+    ) -> Result<Vec<TestCaseResult>> {
+        let markdown_instructions = markdown_into_instructions(page, &Filter::new())?;
+        self.execute(
+            &page.display().to_string(),
+            markdown_instructions,
+            state,
+            tear_down,
+        )
+    }
+
+    /// Executes the instructions table by table, turning a timeout into a failure of the table
+    /// being executed. Nothing else runs once the server is killed, or after a failing table when
+    /// stopping on errors, or after a fixture stops the test. Those tables are reported as skipped.
+    fn execute(
+        &mut self,
+        file_path: &str,
+        markdown_instructions: MarkdownInstructions,
+        state: &mut State,
+        tear_down: bool,
+    ) -> Result<Vec<TestCaseResult>> {
+        let mut results = Vec::new();
+        for batch in test_case_batches(markdown_instructions) {
+            let skip_reason = if self.watchdog.killed() {
+                Some("Skipped after the slim server was killed")
+            } else if tear_down {
+                None
+            } else if let Some(reason) = &self.skip_reason {
+                Some(reason.as_str())
+            } else if self.stopped {
+                Some("Skipped after a failing table")
+            } else {
+                None
+            };
+            if let Some(reason) = skip_reason {
+                let result = skipped_test_case(file_path, &batch.test_case, reason);
+                self.output
+                    .test_case_finished(self.out, &result, self.support_page)?;
+                results.push(result);
+                continue;
+            }
+            let test_case = batch.test_case.clone();
+            let connection = &mut *self.connection;
+            let result = match self
+                .watchdog
+                .run(|| execute_test_case(connection, file_path, batch, state))
+            {
+                Err(err) if err.is::<Timeout>() => {
+                    timed_out_test_case(file_path, &test_case, &err.to_string())
+                }
+                result => result?,
+            };
+            self.output
+                .test_case_finished(self.out, &result, self.support_page)?;
+            self.stopped |= self.stop_on_error && result.failed();
+            match result.abort() {
+                Some(Abort::Suite(reason)) => {
+                    let _ = self.suite_stopped.set(reason.clone());
+                    self.skip_reason
+                        .get_or_insert_with(|| suite_stopped(&reason));
+                }
+                Some(Abort::Test(reason)) => {
+                    self.skip_reason
+                        .get_or_insert_with(|| format!("The test was stopped: {reason}"));
+                }
+                None => {}
+            }
+            results.push(result);
+        }
+        Ok(results)
     }
 }
 
@@ -118,3 +809,506 @@ pub fn get_list_of_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
         .map(|file| file.map(|file| file.path().to_path_buf()))
         .collect::<Result<Vec<PathBuf>, std::io::Error>>()?)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::slim_server_connector::{ExistingSlimServerConnector, ServerAddress};
+    use crate::test_slim_server::TestSlimServerConnector;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use ulid::Ulid;
+
+    fn app(connector: &TestSlimServerConnector) -> App {
+        App::new(
+            false,
+            Format::Text,
+            Box::new(connector.clone()),
+            true,
+            Filter::new(),
+            "md".into(),
+            vec![],
+            vec![],
+            None,
+            1,
+            false,
+            None,
+            Timeouts::default(),
+            false,
+        )
+    }
+
+    /// A decision table of the calculator, with the `a`, `b` and `sum?` cells of each row.
+    fn sum_table(rows: &[[&str; 3]]) -> String {
+        let mut table =
+            "[//]: # (decisionTable Fixtures.Calculator)\n\n| a | b | sum? |\n|---|---|------|\n"
+                .to_string();
+        for [a, b, sum] in rows {
+            table.push_str(&format!("| {a} | {b} | {sum} |\n"));
+        }
+        table
+    }
+
+    fn test_suite_names(app: &App, dir: &Path) -> Vec<String> {
+        app.test_suites
+            .iter()
+            .map(|test_suite| {
+                Path::new(&test_suite.name)
+                    .strip_prefix(dir)
+                    .expect("The files are in the directory")
+                    .display()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn run_the_suite_pages_once_per_directory_tree() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("nested"))?;
+        write(
+            dir.join("SuiteSetUp.md"),
+            sum_table(&[["20", "2", "$BASE="]]),
+        )?;
+        write(dir.join("SuiteTearDown.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("first.md"), sum_table(&[["$BASE", "1", "23"]]))?;
+        write(
+            dir.join("nested/second.md"),
+            sum_table(&[["$BASE", "2", "24"]]),
+        )?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+
+        let fail = app.test(vec![dir.clone()], &mut Vec::new())?;
+
+        let names = test_suite_names(&app, &dir);
+        assert!(!fail, "{names:?}");
+        assert_eq!(4, names.len());
+        assert_eq!("SuiteSetUp.md", names[0]);
+        assert!(names.contains(&"first.md".to_string()));
+        assert!(names.contains(&"nested/second.md".to_string()));
+        assert_eq!("SuiteTearDown.md", names[3]);
+        // One server for the suite pages, and one for each file.
+        assert_eq!(3, connector.started());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn export_the_suite_symbols_into_each_file() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("nested"))?;
+        write(
+            dir.join("SuiteSetUp.md"),
+            sum_table(&[["20", "2", "$BASE="]]),
+        )?;
+        write(
+            dir.join("nested/SuiteSetUp.md"),
+            sum_table(&[["$BASE", "1", "$NESTED="]]),
+        )?;
+        write(
+            dir.join("first.md"),
+            sum_table(&[["$BASE", "1", "23"], ["1", "1", "$BASE="]]),
+        )?;
+        write(
+            dir.join("nested/second.md"),
+            sum_table(&[["$BASE", "$NESTED", "45"]]),
+        )?;
+        write(dir.join("third.md"), sum_table(&[["$NESTED", "1", "24"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+
+        app.test(vec![dir.clone()], &mut Vec::new())?;
+
+        let failures = |name: &str| {
+            app.test_suites
+                .iter()
+                .find(|test_suite| test_suite.name == dir.join(name).display().to_string())
+                .expect("The file was tested")
+                .failures()
+        };
+        // A file can't change the symbols of the suite for the other files.
+        assert_eq!(0, failures("first.md"));
+        assert_eq!(0, failures("nested/second.md"));
+        // The symbols of a nested suite set up are only exported into its own directory tree.
+        assert_eq!(1, failures("third.md"));
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn only_walk_directories_when_recursive() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        write(dir.join("SuiteSetUp.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("first.md"), sum_table(&[["1", "1", "2"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.recursive = false;
+
+        assert!(!app.test(vec![dir.clone()], &mut Vec::new())?);
+
+        assert!(app.test_suites.is_empty());
+        assert_eq!(0, connector.started());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn print_each_file_once_finished_and_keep_the_results_in_order() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        write(
+            dir.join("slow.md"),
+            "[//]: # (script Fixtures.Calculator)\n\n| action |\n|---|\n| block until killed |\n",
+        )?;
+        write(dir.join("fast.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("faster.md"), sum_table(&[["1", "2", "3"]]))?;
+        let files = ["slow.md", "fast.md", "faster.md"].map(|file| dir.join(file));
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.jobs = 2;
+        // Long enough for the other files to finish while the slow one waits to be killed.
+        app.timeouts.batch = Some(Duration::from_secs(1));
+        let mut out = Vec::new();
+
+        assert!(app.test(files.to_vec(), &mut out)?);
+
+        assert_eq!(
+            vec!["slow.md", "fast.md", "faster.md"],
+            test_suite_names(&app, &dir)
+        );
+        let out = String::from_utf8(out)?;
+        let lines: Vec<_> = out.lines().collect();
+        // Each file prints its name, its table and its result, in one piece.
+        let printed: Vec<_> = lines
+            .chunks(3)
+            .take(3)
+            .map(|lines| {
+                let file = lines[0]
+                    .strip_prefix("Testing file ")
+                    .and_then(|line| line.strip_suffix("..."))
+                    .expect("The output of each file starts with its name");
+                assert!(lines[1].ends_with(" failed)"), "{out}");
+                let result = lines[2]
+                    .strip_prefix(&format!("Finished file {file}..."))
+                    .expect("The output of each file ends with its result");
+                (file.to_string(), result)
+            })
+            .collect();
+        assert_eq!(
+            files[1..]
+                .iter()
+                .chain(&files[..1])
+                .map(|file| file.display().to_string())
+                .zip(["OK", "OK", "FAIL"])
+                .collect::<Vec<_>>(),
+            printed
+        );
+        assert_eq!(3, connector.started());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn share_the_single_connection_of_a_running_server_with_every_file_and_suite_page() -> Result<()>
+    {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        write(
+            dir.join("SuiteSetUp.md"),
+            sum_table(&[["20", "2", "$BASE="]]),
+        )?;
+        write(dir.join("SuiteTearDown.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("first.md"), sum_table(&[["$BASE", "1", "23"]]))?;
+        write(dir.join("second.md"), sum_table(&[["$BASE", "2", "24"]]))?;
+        let server = TestSlimServerConnector::default();
+        let (address, thread) = server.serve()?;
+        let mut app = app(&server);
+        app.slim_server_connector = Box::new(ExistingSlimServerConnector::new(ServerAddress::Tcp(
+            address.to_string(),
+        )));
+        app.reuse_server = true;
+
+        let fail = app.test(vec![dir.clone()], &mut Vec::new())?;
+        app.close_shared_session()?;
+
+        let names = test_suite_names(&app, &dir);
+        assert!(!fail, "{names:?}");
+        assert_eq!(4, names.len());
+        // The server stops once the shared connection says goodbye.
+        thread
+            .join()
+            .map_err(|_| anyhow!("The slim server panicked"))??;
+        assert_eq!(1, server.started());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn reuse_the_session_across_files_resetting_it_before_each() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        let files = ["first.md", "second.md", "third.md"].map(|file| dir.join(file));
+        for file in &files {
+            write(file, sum_table(&[["1", "1", "2"]]))?;
+        }
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.reuse_server = true;
+        app.reset_fixture = Some("Fixtures.Reset".into());
+
+        assert!(!app.test(files.to_vec(), &mut Vec::new())?);
+
+        assert_eq!(1, connector.started());
+        let sent = connector.sent();
+        assert_eq!(3, sent.matches("Fixtures.Reset").count());
+        // Each file makes its tables only after resetting the server.
+        let mut after_each_reset = sent.split("Fixtures.Reset");
+        assert!(!after_each_reset
+            .next()
+            .is_some_and(|before| before.contains("Fixtures.Calculator")));
+        assert!(after_each_reset.all(|file| file.contains("Fixtures.Calculator")));
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn drop_the_session_after_an_error() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        let file = dir.join("calculator.md");
+        write(&file, sum_table(&[["1", "1", "2"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.reuse_server = true;
+        app.reset_fixture = Some("Fixtures.Missing".into());
+        let mut session = None;
+
+        assert!(app
+            .process_file(&file, Filter::new(), &mut Vec::new(), &mut session)
+            .is_err());
+        assert!(session.is_none());
+
+        app.reset_fixture = Some("Fixtures.Reset".into());
+        app.process_file(&file, Filter::new(), &mut Vec::new(), &mut session)?;
+        assert!(session.is_some());
+        app.process_file(&file, Filter::new(), &mut Vec::new(), &mut session)?;
+        assert_eq!(2, connector.started());
+        session.map(SlimSession::close).transpose()?;
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn time_out_a_table_and_test_the_next_file_on_a_new_server() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        let slow = dir.join("slow.md");
+        write(
+            &slow,
+            "[//]: # (script Fixtures.Calculator)\n\n| action |\n|---|\n| block until killed |\n",
+        )?;
+        let fast = dir.join("fast.md");
+        write(&fast, sum_table(&[["1", "1", "2"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.reuse_server = true;
+        app.timeouts.batch = Some(Duration::from_millis(100));
+
+        assert!(app.test(vec![slow, fast], &mut Vec::new())?);
+
+        assert_eq!(1, app.test_suites[0].failures());
+        assert_eq!(0, app.test_suites[1].failures());
+        assert_eq!(2, connector.started());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    fn skipped(app: &App) -> Vec<Option<String>> {
+        app.test_suites
+            .iter()
+            .flat_map(|test_suite| &test_suite.test_cases)
+            .map(|test_case| test_case.skipped.clone())
+            .collect()
+    }
+
+    #[test]
+    fn report_the_tables_after_a_failing_one_as_skipped() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        let file = dir.join("calculator.md");
+        write(
+            &file,
+            [["1", "1", "3"], ["1", "1", "2"], ["1", "2", "3"]]
+                .map(|row| sum_table(&[row]))
+                .join("\n"),
+        )?;
+        write(dir.join("TearDown.md"), sum_table(&[["2", "2", "4"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.stop_on_error = true;
+
+        assert!(app.test(vec![file], &mut Vec::new())?);
+
+        let skipped = Some("Skipped after a failing table".to_string());
+        assert_eq!(
+            vec![None, skipped.clone(), skipped, None],
+            self::skipped(&app)
+        );
+        assert_eq!(2, app.test_suites[0].skipped());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn report_the_tables_after_a_kill_as_skipped() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        let file = dir.join("calculator.md");
+        write(
+            &file,
+            format!(
+                "{}\n[//]: # (script Fixtures.Calculator)\n\n| action |\n|---|\n| block until killed |\n\n{}",
+                sum_table(&[["1", "1", "2"]]),
+                sum_table(&[["1", "2", "3"]])
+            ),
+        )?;
+        write(dir.join("TearDown.md"), sum_table(&[["2", "2", "4"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.timeouts.batch = Some(Duration::from_millis(100));
+
+        assert!(app.test(vec![file], &mut Vec::new())?);
+
+        let skipped = Some("Skipped after the slim server was killed".to_string());
+        assert_eq!(
+            vec![None, None, skipped.clone(), skipped],
+            self::skipped(&app)
+        );
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn find_the_files_left_unfinished_by_a_cancel() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("nested"))?;
+        for file in [
+            "SetUp.md",
+            "first.md",
+            "second.md",
+            "nested/third.md",
+            "notes.txt",
+        ] {
+            write(dir.join(file), sum_table(&[["1", "1", "2"]]))?;
+        }
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+        app.test(
+            vec![dir.join("first.md"), dir.join("nested/third.md")],
+            &mut Vec::new(),
+        )?;
+
+        let mut unfinished = app.unfinished_files(vec![dir.clone(), dir.join("second.md:5")])?;
+
+        unfinished.sort();
+        assert_eq!(
+            vec![dir.join("second.md"), dir.join("second.md:5")],
+            unfinished
+        );
+        app.recursive = false;
+        assert!(app.unfinished_files(vec![dir.clone()])?.is_empty());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    fn test_case_files(app: &App, name: &Path) -> Vec<PathBuf> {
+        app.test_suites
+            .iter()
+            .find(|test_suite| test_suite.name == name.display().to_string())
+            .expect("The file was tested")
+            .test_cases
+            .iter()
+            .map(|test_case| PathBuf::from(&test_case.file))
+            .collect()
+    }
+
+    #[test]
+    fn find_the_set_up_and_tear_down_up_to_the_directory_argument() -> Result<()> {
+        let outer = temp_dir().join(Ulid::new().to_string());
+        let dir = outer.join("tests");
+        create_dir_all(dir.join("nested"))?;
+        create_dir_all(dir.join("own"))?;
+        write(outer.join("TearDown.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("SetUp.md"), sum_table(&[["1", "1", "$BASE="]]))?;
+        write(
+            dir.join("nested/deeper.md"),
+            sum_table(&[["$BASE", "1", "3"]]),
+        )?;
+        write(dir.join("own/SetUp.md"), sum_table(&[["5", "5", "$BASE="]]))?;
+        write(dir.join("own/TearDown.md"), sum_table(&[["2", "2", "4"]]))?;
+        write(
+            dir.join("own/calculator.md"),
+            sum_table(&[["$BASE", "1", "11"]]),
+        )?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+
+        assert!(!app.test(vec![dir.clone()], &mut Vec::new())?);
+
+        assert_eq!(
+            vec![dir.join("SetUp.md"), dir.join("nested/deeper.md")],
+            test_case_files(&app, &dir.join("nested/deeper.md"))
+        );
+        assert_eq!(
+            vec![
+                dir.join("own/SetUp.md"),
+                dir.join("own/calculator.md"),
+                dir.join("own/TearDown.md")
+            ],
+            test_case_files(&app, &dir.join("own/calculator.md"))
+        );
+        // A file given on its own doesn't look above its directory.
+        assert!(app.test(vec![dir.join("nested/deeper.md")], &mut Vec::new())?);
+        assert_eq!(
+            vec![dir.join("nested/deeper.md")],
+            test_case_files(&app, &dir.join("nested/deeper.md"))
+        );
+        remove_dir_all(outer)?;
+        Ok(())
+    }
+
+    #[test]
+    fn run_the_tear_down_after_a_failing_test_without_reporting_the_pages_as_tests() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        write(dir.join("SetUp.md"), sum_table(&[["1", "1", "2"]]))?;
+        write(dir.join("TearDown.md"), sum_table(&[["2", "2", "4"]]))?;
+        write(dir.join("calculator.md"), sum_table(&[["1", "1", "3"]]))?;
+        let connector = TestSlimServerConnector::default();
+        let mut app = app(&connector);
+
+        assert!(app.test(vec![dir.clone()], &mut Vec::new())?);
+
+        assert_eq!(vec!["calculator.md"], test_suite_names(&app, &dir));
+        let test_cases = &app.test_suites[0].test_cases;
+        assert_eq!(
+            vec![
+                dir.join("SetUp.md"),
+                dir.join("calculator.md"),
+                dir.join("TearDown.md")
+            ],
+            test_case_files(&app, &dir.join("calculator.md"))
+        );
+        assert_eq!(
+            vec![false, true, false],
+            test_cases
+                .iter()
+                .map(|test_case| test_case.failed())
+                .collect::<Vec<_>>()
+        );
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+}
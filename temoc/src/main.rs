@@ -1,15 +1,29 @@
 use crate::app::{get_list_of_files, App};
+use crate::output::Format;
 use crate::processor::Filter;
-use crate::slim_server_connector::build_slim_server_connector;
+use crate::report::Report;
+use crate::slim_server_connector::{
+    build_slim_server_connector, ExistingSlimServerConnector, ServerAddress,
+};
+use crate::update::Update;
+use crate::watchdog::Timeouts;
 use anyhow::{anyhow, bail, Result};
-use clap::Parser;
-use std::{fs::read_to_string, path::PathBuf};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+use std::{fs::read_to_string, path::PathBuf, time::Duration};
 use toml::Table;
 
 mod app;
+mod output;
 mod port;
 mod processor;
+mod report;
 mod slim_server_connector;
+#[cfg(test)]
+mod test_slim_server;
+mod update;
+mod watch;
+mod watchdog;
 
 /// Test markdown files using a slim server
 #[derive(Parser, Debug)]
@@ -27,6 +41,9 @@ struct Args {
     /// Command to start the slim server
     #[arg(short = 'x', long)]
     execute_server_command: Option<String>,
+    /// Connect to a slim server that is already running instead of starting one, sharing its single connection with every file. Needs `--reuse-server` and one job. Eg: `localhost:8085` or `unix:/tmp/slim.sock`
+    #[arg(long)]
+    connect: Option<ServerAddress>,
     /// Recursively traverse files and directories to test
     #[arg(short, long)]
     recursive: bool,
@@ -42,14 +59,81 @@ struct Args {
     /// Filter the decision tables by the fixture class. Accept any regex string
     #[arg(short = 'f', long)]
     class_filter: Option<String>,
+    /// Format of the output. `json` prints one event per line
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Write a report of the results once all files are tested. Eg: `junit=report.xml` or `html=report_dir`
+    #[arg(long)]
+    report: Vec<Report>,
+    /// Write the actual values of the failing cells back into the markdown files
+    #[arg(long, conflicts_with = "update_only_empty")]
+    update: bool,
+    /// Write the actual values only into the cells left blank in the markdown files
+    #[arg(long)]
+    update_only_empty: bool,
+    /// Number of files tested at the same time, each with its own slim server. Default is 1
+    #[arg(short, long)]
+    jobs: Option<usize>,
+    /// Keep the slim server alive across the files tested by each job, instead of starting one per file
+    #[arg(long)]
+    reuse_server: bool,
+    /// Fixture made at the start of each file so it can clear the state left in a reused server
+    #[arg(long, requires = "reuse_server")]
+    reset_fixture: Option<String>,
+    /// Seconds to wait for the results of each batch of instructions before killing the slim server
+    #[arg(long)]
+    batch_timeout: Option<u64>,
+    /// Seconds a file, with its set up and tear down, can take before killing the slim server
+    #[arg(long)]
+    file_timeout: Option<u64>,
+    /// Stop testing a file at its first failing table. Its tear down still runs
+    #[arg(long)]
+    stop_on_error: bool,
+    /// Keep testing again the files that change, or all of them when the fixture sources change
+    #[arg(long)]
+    watch: bool,
+    /// Sources of the fixtures to watch for changes. Eg: `src`
+    #[arg(long, requires = "watch")]
+    watch_source: Vec<PathBuf>,
     /// List of files to test
     files: Vec<PathBuf>,
 }
 
 fn main() -> Result<()> {
     let args = append_config_to_args(Args::parse())?;
-    let Some(command) = args.execute_server_command else {
-        bail!("You need to provide a command to start the slim server")
+    // Clap only checks the command line, so a reset fixture from the config file is checked here.
+    if args.reset_fixture.is_some() && !args.reuse_server {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  --reuse-server",
+            )
+            .exit();
+    }
+    let jobs = args.jobs.unwrap_or(1);
+    if jobs == 0 {
+        bail!("You need to run at least one job");
+    }
+    // A running slim server stops once its connection says goodbye, so everything shares it.
+    if args.connect.is_some() && jobs > 1 {
+        bail!("You can only run one job when connecting to a running slim server");
+    }
+    if args.connect.is_some() && !args.reuse_server {
+        bail!("You need to reuse the server when connecting to a running slim server");
+    }
+    let slim_server_connector = match args.connect {
+        Some(address) => Box::new(ExistingSlimServerConnector::new(address)),
+        None => {
+            let Some(command) = args.execute_server_command else {
+                bail!("You need to provide a command to start the slim server")
+            };
+            build_slim_server_connector(
+                command,
+                args.port.unwrap_or(8085),
+                args.pool_size.unwrap_or(20),
+                args.pipe_output,
+            )
+        }
     };
 
     let mut filter = Filter::new();
@@ -57,21 +141,32 @@ fn main() -> Result<()> {
         filter = filter.fixture_class(&fixture)?;
     }
 
-    if App::new(
+    let app = App::new(
         args.show_snoozed,
-        build_slim_server_connector(
-            command,
-            args.port.unwrap_or(8085),
-            args.pool_size.unwrap_or(20),
-            args.pipe_output,
-        ),
+        args.format.unwrap_or_default(),
+        slim_server_connector,
         args.recursive,
         filter,
         args.extension.unwrap_or("md".to_string()).to_lowercase(),
         args.files,
-    )
-    .run()?
-    {
+        args.report,
+        match (args.update, args.update_only_empty) {
+            (true, _) => Some(Update::All),
+            (false, true) => Some(Update::OnlyEmpty),
+            (false, false) => None,
+        },
+        jobs,
+        args.reuse_server,
+        args.reset_fixture,
+        Timeouts {
+            batch: args.batch_timeout.map(Duration::from_secs),
+            file: args.file_timeout.map(Duration::from_secs),
+        },
+        args.stop_on_error,
+    );
+    if args.watch {
+        app.watch(args.watch_source)?;
+    } else if app.run()? {
         bail!("Tests executed with error");
     }
 
@@ -124,6 +219,18 @@ fn append_config_to_args(mut args: Args) -> Result<Args> {
                         .expect("Expect the slim server command to be a string")
                         .to_string()
                 }));
+            args.connect = match args.connect {
+                Some(address) => Some(address),
+                None => config_file
+                    .get("connect")
+                    .map(|address| {
+                        address
+                            .as_str()
+                            .expect("Expect the connect address to be a string")
+                            .parse()
+                    })
+                    .transpose()?,
+            };
             args.extension = args
                 .extension
                 .or(config_file.get("extension").map(|command| {
@@ -132,6 +239,109 @@ fn append_config_to_args(mut args: Args) -> Result<Args> {
                         .expect("Expect the slim server command to be a string")
                         .to_string()
                 }));
+            args.jobs = args.jobs.or(config_file
+                .get("jobs")
+                .map(|jobs| jobs.as_integer().expect("Expect the jobs to be a number") as usize));
+            args.reuse_server = args.reuse_server
+                || config_file
+                    .get("reuse_server")
+                    .map(|reuse_server| {
+                        reuse_server
+                            .as_bool()
+                            .expect("Expect the reuse_server to be a boolean")
+                    })
+                    .unwrap_or_default();
+            args.reset_fixture =
+                args.reset_fixture
+                    .or(config_file.get("reset_fixture").map(|reset_fixture| {
+                        reset_fixture
+                            .as_str()
+                            .expect("Expect the reset fixture to be a string")
+                            .to_string()
+                    }));
+            args.batch_timeout =
+                args.batch_timeout
+                    .or(config_file.get("batch_timeout").map(|timeout| {
+                        timeout
+                            .as_integer()
+                            .expect("Expect the batch timeout to be a number")
+                            as u64
+                    }));
+            args.file_timeout =
+                args.file_timeout
+                    .or(config_file.get("file_timeout").map(|timeout| {
+                        timeout
+                            .as_integer()
+                            .expect("Expect the file timeout to be a number")
+                            as u64
+                    }));
+            args.stop_on_error = args.stop_on_error
+                || config_file
+                    .get("stop_on_error")
+                    .map(|stop_on_error| {
+                        stop_on_error
+                            .as_bool()
+                            .expect("Expect the stop_on_error to be a boolean")
+                    })
+                    .unwrap_or_default();
+            args.watch = args.watch
+                || config_file
+                    .get("watch")
+                    .map(|watch| watch.as_bool().expect("Expect the watch to be a boolean"))
+                    .unwrap_or_default();
+            if args.watch_source.is_empty() {
+                args.watch_source = match config_file.get("watch_source") {
+                    None => vec![],
+                    Some(source) => match source.as_array() {
+                        Some(sources) => sources
+                            .iter()
+                            .map(|source| {
+                                PathBuf::from(
+                                    source
+                                        .as_str()
+                                        .expect("Expect the watch source to be a string"),
+                                )
+                            })
+                            .collect(),
+                        None => vec![PathBuf::from(source.as_str().expect(
+                            "Expect the watch source to be a string or a list of strings",
+                        ))],
+                    },
+                };
+            }
+            args.format = match args.format {
+                Some(format) => Some(format),
+                None => config_file
+                    .get("format")
+                    .map(|format| {
+                        Format::from_str(
+                            format.as_str().expect("Expect the format to be a string"),
+                            true,
+                        )
+                        .map_err(|err| anyhow!(err))
+                    })
+                    .transpose()?,
+            };
+            if args.report.is_empty() {
+                args.report = match config_file.get("report") {
+                    None => vec![],
+                    Some(report) => match report.as_array() {
+                        Some(reports) => reports
+                            .iter()
+                            .map(|report| {
+                                report
+                                    .as_str()
+                                    .expect("Expect the report to be a string")
+                                    .parse()
+                            })
+                            .collect::<Result<Vec<Report>>>()?,
+                        None => vec![report
+                            .as_str()
+                            .expect("Expect the report to be a string or a list of strings")
+                            .parse()?],
+                    },
+                };
+            }
             args.files = if !args.files.is_empty() {
                 args.files
             } else {
@@ -0,0 +1,475 @@
+use crate::processor::{print_fail_or_ok, CheckedResult, TestCaseResult};
+use crate::report::TestSuiteResult;
+use crate::watch::Changes;
+use anyhow::{Error, Result};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use slim_protocol::InstructionResultValue;
+use std::io::Write;
+use std::path::Path;
+
+/// Format used to print the progress and the results of the tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Format {
+    /// Human readable results
+    #[default]
+    Text,
+    /// One JSON event per line
+    Json,
+}
+
+pub struct Output {
+    format: Format,
+    show_snoozed: bool,
+}
+
+impl Output {
+    pub fn new(format: Format, show_snoozed: bool) -> Self {
+        Self {
+            format,
+            show_snoozed,
+        }
+    }
+
+    /// Support pages, like the suite set up, are only printed as text when they fail.
+    pub fn file_started(
+        &self,
+        out: &mut impl Write,
+        file: &Path,
+        support_page: bool,
+    ) -> Result<()> {
+        match self.format {
+            Format::Text if !support_page => writeln!(out, "Testing file {}...", file.display())?,
+            Format::Text => {}
+            Format::Json => write_event(
+                out,
+                json!({
+                    "event": "file_started",
+                    "file": file.display().to_string(),
+                }),
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Streams the results of each table as soon as it is executed. The tables of the support
+    /// pages are only printed as events.
+    pub fn test_case_finished(
+        &self,
+        out: &mut impl Write,
+        test_case: &TestCaseResult,
+        support_page: bool,
+    ) -> Result<()> {
+        match self.format {
+            Format::Text if !support_page => writeln!(out, "  {}", test_case_line(test_case))?,
+            Format::Text => {}
+            Format::Json => {
+                for event in test_case_events(test_case) {
+                    write_event(out, event)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the results of the file and returns if any of them failed without being snoozed.
+    pub fn file_finished(
+        &self,
+        out: &mut impl Write,
+        test_suite: &TestSuiteResult,
+        support_page: bool,
+    ) -> Result<bool> {
+        match self.format {
+            Format::Text => {
+                if support_page {
+                    if test_suite
+                        .test_cases
+                        .iter()
+                        .all(|test_case| test_case.failures().is_empty())
+                    {
+                        return Ok(false);
+                    }
+                    write!(out, "Running {}...", test_suite.name)?;
+                } else {
+                    write!(out, "Finished file {}...", test_suite.name)?;
+                }
+                if test_suite.test_cases.is_empty() {
+                    writeln!(out, "NONE")?;
+                    return Ok(false);
+                }
+                print_fail_or_ok(out, self.show_snoozed, &test_suite.test_cases)
+            }
+            Format::Json => {
+                write_event(out, file_finished_event(test_suite))?;
+                Ok(test_suite.failures() > 0)
+            }
+        }
+    }
+
+    pub fn file_updated(
+        &self,
+        out: &mut impl Write,
+        file: &Path,
+        updated_cells: usize,
+    ) -> Result<()> {
+        match self.format {
+            Format::Text => writeln!(
+                out,
+                "Updated {updated_cells} cell(s) of {} with the actual values",
+                file.display()
+            )?,
+            Format::Json => write_event(
+                out,
+                json!({
+                    "event": "file_updated",
+                    "file": file.display().to_string(),
+                    "cells": updated_cells,
+                }),
+            )?,
+        }
+        Ok(())
+    }
+
+    pub fn watching(&self, out: &mut impl Write) -> Result<()> {
+        match self.format {
+            Format::Text => writeln!(out, "Watching for changes...")?,
+            Format::Json => write_event(out, json!({ "event": "watching" }))?,
+        }
+        Ok(())
+    }
+
+    /// The file being tested when a change arrived is left without its results.
+    pub fn cancelled(&self, out: &mut impl Write) -> Result<()> {
+        match self.format {
+            Format::Text => writeln!(out, "CANCELLED")?,
+            Format::Json => write_event(out, json!({ "event": "cancelled" }))?,
+        }
+        Ok(())
+    }
+
+    /// An error that stopped the tests, like a table that can't be parsed, while watching.
+    pub fn error(&self, out: &mut impl Write, error: &Error) -> Result<()> {
+        match self.format {
+            Format::Text => writeln!(out, "Error: {error:?}")?,
+            Format::Json => write_event(
+                out,
+                json!({
+                    "event": "error",
+                    "message": format!("{error:#}"),
+                }),
+            )?,
+        }
+        Ok(())
+    }
+
+    pub fn changes_detected(&self, out: &mut impl Write, changes: &Changes) -> Result<()> {
+        let files: Vec<String> = changes
+            .tests
+            .iter()
+            .chain(&changes.sources)
+            .map(|file| file.display().to_string())
+            .collect();
+        match self.format {
+            Format::Text => writeln!(out, "Changed {}, testing again...", files.join(", "))?,
+            Format::Json => write_event(
+                out,
+                json!({
+                    "event": "changes_detected",
+                    "files": files,
+                }),
+            )?,
+        }
+        Ok(())
+    }
+
+    pub fn summary(&self, out: &mut impl Write, test_suites: &[TestSuiteResult]) -> Result<()> {
+        if self.format == Format::Json {
+            write_event(out, summary_event(test_suites))?;
+        }
+        Ok(())
+    }
+}
+
+fn write_event(out: &mut impl Write, event: Value) -> Result<()> {
+    writeln!(out, "{event}")?;
+    Ok(())
+}
+
+/// The table with its location, and how many of its results passed or failed.
+fn test_case_line(test_case: &TestCaseResult) -> String {
+    let table = format!("{} at {}", test_case.name, test_case.location());
+    if test_case.skipped.is_some() {
+        return format!("{table}...SKIPPED");
+    }
+    let failed = test_case
+        .results
+        .iter()
+        .filter(|result| !result.passed())
+        .count();
+    let status = if test_case.failed() {
+        "FAIL"
+    } else if failed > 0 {
+        "SNOOZED"
+    } else {
+        "OK"
+    };
+    format!(
+        "{table}...{status} ({} passed, {failed} failed)",
+        test_case.results.len() - failed
+    )
+}
+
+fn test_case_events(test_case: &TestCaseResult) -> Vec<Value> {
+    if let Some(reason) = &test_case.skipped {
+        return vec![json!({
+            "event": "table_skipped",
+            "file": test_case.file,
+            "table": test_case.name,
+            "line": test_case.position.line(),
+            "column": test_case.position.column(),
+            "location": test_case.location(),
+            "reason": reason,
+        })];
+    }
+    let mut events = vec![json!({
+        "event": "table_started",
+        "file": test_case.file,
+        "table": test_case.name,
+        "line": test_case.position.line(),
+        "column": test_case.position.column(),
+        "location": test_case.location(),
+    })];
+    for result in &test_case.results {
+        events.push(result_event("row_result", test_case, result, |event| {
+            event["passed"] = json!(result.passed());
+        }));
+        for failure in &result.failures {
+            if result.snooze.should_snooze() {
+                events.push(result_event("snooze", test_case, result, |event| {
+                    event["message"] = json!(failure.message);
+                    event["until"] = json!(result.snooze.to_string());
+                }));
+            } else {
+                events.push(result_event("failure", test_case, result, |event| {
+                    event["message"] = json!(failure.message);
+                }));
+            }
+        }
+    }
+    events
+}
+
+fn file_finished_event(test_suite: &TestSuiteResult) -> Value {
+    json!({
+        "event": "file_finished",
+        "file": test_suite.name,
+        "tests": test_suite.test_cases.len(),
+        "failures": test_suite.failures(),
+        "snoozed": test_suite.snoozed(),
+        "skipped": test_suite.skipped(),
+        "passed": test_suite.failures() == 0,
+        "time": test_suite.time.as_secs_f64(),
+    })
+}
+
+fn result_event(
+    name: &str,
+    test_case: &TestCaseResult,
+    result: &CheckedResult,
+    extra_fields: impl FnOnce(&mut Value),
+) -> Value {
+    let position = &result.expected.position;
+    let mut event = json!({
+        "event": name,
+        "file": test_case.file,
+        "table": test_case.name,
+        "line": position.line(),
+        "column": position.column(),
+        "location": position.in_file(&test_case.file).to_string(),
+        "method": result.expected.method_name.as_ref().map(|method_name| &method_name.0),
+        "expected": result.expected.value.to_string(),
+        "actual": instruction_result_value_to_json(&result.actual),
+    });
+    extra_fields(&mut event);
+    event
+}
+
+fn instruction_result_value_to_json(value: &InstructionResultValue) -> Value {
+    match value {
+        InstructionResultValue::Ok => json!({ "type": "ok" }),
+        InstructionResultValue::Void => json!({ "type": "void" }),
+        InstructionResultValue::Exception(message) => json!({
+            "type": "exception",
+            "message": message.pretty_message().unwrap_or(message.raw_message()),
+        }),
+        InstructionResultValue::String(value) => json!({ "type": "string", "value": value }),
+        InstructionResultValue::List(values) => json!({
+            "type": "list",
+            "value": values
+                .iter()
+                .map(instruction_result_value_to_json)
+                .collect::<Vec<Value>>(),
+        }),
+    }
+}
+
+fn summary_event(test_suites: &[TestSuiteResult]) -> Value {
+    let failures = test_suites
+        .iter()
+        .map(TestSuiteResult::failures)
+        .sum::<usize>();
+    json!({
+        "event": "summary",
+        "files": test_suites.len(),
+        "tests": test_suites
+            .iter()
+            .map(|test_suite| test_suite.test_cases.len())
+            .sum::<usize>(),
+        "failures": failures,
+        "snoozed": test_suites
+            .iter()
+            .map(TestSuiteResult::snoozed)
+            .sum::<usize>(),
+        "skipped": test_suites
+            .iter()
+            .map(TestSuiteResult::skipped)
+            .sum::<usize>(),
+        "passed": failures == 0,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::{ExpectedResult, Failure, Position, Snooze};
+    use slim_protocol::Id;
+    use std::time::Duration;
+
+    #[test]
+    fn json_events() {
+        let test_suite = TestSuiteResult {
+            name: "calculator.md".into(),
+            test_cases: vec![TestCaseResult {
+                name: "Calculator".into(),
+                file: "calculator.md".into(),
+                position: Position::new(3, 1),
+                results: vec![
+                    CheckedResult {
+                        expected: ExpectedResult::ok(Id::new(), Position::new(3, 1)),
+                        actual: InstructionResultValue::Ok,
+                        snooze: Snooze::not_snooze(),
+                        failures: vec![],
+                    },
+                    CheckedResult {
+                        expected: ExpectedResult::null_or_void(
+                            Id::new(),
+                            Position::new(5, 3),
+                            None,
+                        ),
+                        actual: InstructionResultValue::List(vec![
+                            InstructionResultValue::String("a".into()),
+                            InstructionResultValue::Void,
+                        ]),
+                        snooze: Snooze::not_snooze(),
+                        failures: vec![Failure {
+                            position: Position::new(5, 3),
+                            message: "Expected NULL or VOID".into(),
+                        }],
+                    },
+                ],
+                skipped: None,
+            }],
+            time: Duration::from_millis(250),
+        };
+        assert_eq!(
+            vec![
+                json!({"event": "table_started", "file": "calculator.md", "table": "Calculator", "line": 3, "column": 1, "location": "calculator.md:3:1"}),
+                json!({"event": "row_result", "file": "calculator.md", "table": "Calculator", "line": 3, "column": 1, "location": "calculator.md:3:1", "method": null, "expected": "OK", "actual": {"type": "ok"}, "passed": true}),
+                json!({"event": "row_result", "file": "calculator.md", "table": "Calculator", "line": 5, "column": 3, "location": "calculator.md:5:3", "method": null, "expected": "NULL or VOID", "actual": {"type": "list", "value": [{"type": "string", "value": "a"}, {"type": "void"}]}, "passed": false}),
+                json!({"event": "failure", "file": "calculator.md", "table": "Calculator", "line": 5, "column": 3, "location": "calculator.md:5:3", "method": null, "expected": "NULL or VOID", "actual": {"type": "list", "value": [{"type": "string", "value": "a"}, {"type": "void"}]}, "message": "Expected NULL or VOID"}),
+            ],
+            test_case_events(&test_suite.test_cases[0])
+        );
+        assert_eq!(
+            json!({"event": "file_finished", "file": "calculator.md", "tests": 1, "failures": 1, "snoozed": 0, "skipped": 0, "passed": false, "time": 0.25}),
+            file_finished_event(&test_suite)
+        );
+        assert_eq!(
+            json!({"event": "summary", "files": 1, "tests": 1, "failures": 1, "snoozed": 0, "skipped": 0, "passed": false}),
+            summary_event(std::slice::from_ref(&test_suite))
+        );
+
+        let stopped = TestSuiteResult {
+            test_cases: vec![
+                test_suite.test_cases[0].clone(),
+                TestCaseResult {
+                    results: vec![],
+                    skipped: Some("The test was stopped: Database is down".into()),
+                    ..test_suite.test_cases[0].clone()
+                },
+            ],
+            ..test_suite
+        };
+        assert_eq!(
+            json!({"event": "summary", "files": 1, "tests": 2, "failures": 1, "snoozed": 0, "skipped": 1, "passed": false}),
+            summary_event(&[stopped])
+        );
+    }
+
+    #[test]
+    fn text_line_per_table() -> Result<()> {
+        let passed = CheckedResult {
+            expected: ExpectedResult::ok(Id::new(), Position::new(3, 1)),
+            actual: InstructionResultValue::Ok,
+            snooze: Snooze::not_snooze(),
+            failures: vec![],
+        };
+        let failed = CheckedResult {
+            failures: vec![Failure {
+                position: Position::new(5, 3),
+                message: "Expected 3 but got 2".into(),
+            }],
+            ..passed.clone()
+        };
+        let test_case = TestCaseResult {
+            name: "Calculator".into(),
+            file: "calculator.md".into(),
+            position: Position::new(3, 1),
+            results: vec![passed.clone(), passed, failed],
+            skipped: None,
+        };
+        let output = Output::new(Format::Text, false);
+        let mut out = Vec::new();
+        output.test_case_finished(&mut out, &test_case, false)?;
+        output.test_case_finished(
+            &mut out,
+            &TestCaseResult {
+                results: vec![],
+                skipped: Some("Skipped after a failing table".into()),
+                ..test_case.clone()
+            },
+            false,
+        )?;
+        output.test_case_finished(&mut out, &test_case, true)?;
+        assert_eq!(
+            "  Calculator at calculator.md:3:1...FAIL (2 passed, 1 failed)\n  Calculator at calculator.md:3:1...SKIPPED\n",
+            String::from_utf8(out)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_event() -> Result<()> {
+        let error = anyhow::anyhow!("Invalid table").context("Failed to parse calculator.md");
+        let mut out = Vec::new();
+        Output::new(Format::Json, false).error(&mut out, &error)?;
+        assert_eq!(
+            json!({"event": "error", "message": "Failed to parse calculator.md: Invalid table"}),
+            serde_json::from_slice::<Value>(&out)?
+        );
+        let mut out = Vec::new();
+        Output::new(Format::Text, false).error(&mut out, &error)?;
+        assert!(String::from_utf8(out)?.starts_with("Error: Failed to parse calculator.md"));
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fmt::Debug;
 
 #[derive(Debug, Clone)]
@@ -23,7 +24,30 @@ impl CyclePort {
         }
     }
 
-    pub fn to_port(&self) -> u16 {
-        self.port
+    /// Moves to the next port that is not in use, going around the pool at most once.
+    pub fn new_port_not_in(&mut self, in_use: &HashSet<u16>) -> Option<u16> {
+        for _ in 0..=self.pool_size {
+            self.new_port();
+            if !in_use.contains(&self.port) {
+                return Some(self.port);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn skip_the_ports_in_use() {
+        let mut port = CyclePort::new(8086, 8085, 2);
+        assert_eq!(Some(8087), port.new_port_not_in(&HashSet::new()));
+        assert_eq!(Some(8086), port.new_port_not_in(&HashSet::from([8085])));
+        assert_eq!(
+            None,
+            port.new_port_not_in(&HashSet::from([8085, 8086, 8087]))
+        );
     }
 }
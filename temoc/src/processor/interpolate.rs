@@ -0,0 +1,198 @@
+use slim_protocol::Instruction;
+
+/// The instruction with the `$name` and `${name}` symbols of its class name and arguments replaced
+/// by their values, or the name of the first symbol without a value.
+pub fn interpolate_instruction<'a>(
+    instruction: &Instruction,
+    symbol: impl Fn(&str) -> Option<&'a String>,
+) -> Result<Instruction, String> {
+    let args = |args: &[String]| -> Result<Vec<String>, String> {
+        args.iter().map(|arg| interpolate(arg, &symbol)).collect()
+    };
+    Ok(match instruction {
+        Instruction::Make {
+            id,
+            instance,
+            class,
+            args: make_args,
+        } => Instruction::Make {
+            id: id.clone(),
+            instance: instance.clone(),
+            class: interpolate(class, &symbol)?,
+            args: args(make_args)?,
+        },
+        Instruction::Call {
+            id,
+            instance,
+            function,
+            args: call_args,
+        } => Instruction::Call {
+            id: id.clone(),
+            instance: instance.clone(),
+            function: function.clone(),
+            args: args(call_args)?,
+        },
+        Instruction::CallAndAssign {
+            id,
+            symbol: assigned,
+            instance,
+            function,
+            args: call_args,
+        } => Instruction::CallAndAssign {
+            id: id.clone(),
+            symbol: assigned.clone(),
+            instance: instance.clone(),
+            function: function.clone(),
+            args: args(call_args)?,
+        },
+        other => other.clone(),
+    })
+}
+
+/// A `$` not followed by a symbol name, like in `$5`, is kept as it is, while `$$` is a literal `$`
+/// so `$$name` stays `$name`.
+fn interpolate<'a>(
+    value: &str,
+    symbol: &impl Fn(&str) -> Option<&'a String>,
+) -> Result<String, String> {
+    let mut interpolated = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        interpolated.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(next) = after.strip_prefix('$') {
+            interpolated.push('$');
+            rest = next;
+            continue;
+        }
+        let (name, next) = match after
+            .strip_prefix('{')
+            .and_then(|braced| braced.split_once('}'))
+        {
+            Some(braced) => braced,
+            None => after.split_at(
+                after
+                    .find(|c: char| !is_name_char(c))
+                    .unwrap_or(after.len()),
+            ),
+        };
+        if !is_symbol_name(name) {
+            interpolated.push('$');
+            rest = after;
+            continue;
+        }
+        interpolated.push_str(symbol(name).ok_or_else(|| name.to_string())?);
+        rest = next;
+    }
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// An expected cell with the escaped `$$` turned into a literal `$`, as it is never interpolated.
+pub fn unescape(value: &str) -> String {
+    value.replace("$$", "$")
+}
+
+/// The symbol of a cell that is only `$name`, unless escaped as `$$name`.
+pub fn cell_symbol(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('$')
+        .filter(|name| !name.starts_with('$'))
+}
+
+fn is_symbol_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(is_name_char)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use slim_protocol::Id;
+    use std::collections::HashMap;
+
+    #[test]
+    fn interpolate_symbols() {
+        let symbols = HashMap::from([
+            ("ID".to_string(), "42".to_string()),
+            ("user_name".to_string(), "ada".to_string()),
+        ]);
+        let symbol = |name: &str| symbols.get(name);
+        assert_eq!(Ok("42".into()), interpolate("$ID", &symbol));
+        assert_eq!(
+            Ok("user-42@example.com".into()),
+            interpolate("user-$ID@example.com", &symbol)
+        );
+        assert_eq!(Ok("ada42".into()), interpolate("${user_name}$ID", &symbol));
+        assert_eq!(Ok("42_x".into()), interpolate("${ID}_x", &symbol));
+        assert_eq!(
+            Ok("costs $5 or $ or ${}".into()),
+            interpolate("costs $5 or $ or ${}", &symbol)
+        );
+        assert_eq!(
+            Ok("$ID is 42, $${ID} is ${ID}".into()),
+            interpolate("$$ID is $ID, $$$${ID} is $${ID}", &symbol)
+        );
+        assert_eq!(Ok("$MISSING".into()), interpolate("$$MISSING", &symbol));
+        assert_eq!(Err("ID_x".into()), interpolate("$ID_x", &symbol));
+        assert_eq!(Err("NAME".into()), interpolate("Hi ${NAME}!", &symbol));
+    }
+
+    #[test]
+    fn interpolate_class_names_and_arguments() {
+        let symbols = HashMap::from([("ENV".to_string(), "Staging".to_string())]);
+        let id = Id::new();
+        assert_eq!(
+            Ok(Instruction::Make {
+                id: id.clone(),
+                instance: "$ENV".into(),
+                class: "Fixtures.Staging".into(),
+                args: vec!["Staging".into(), "db-Staging".into()],
+            }),
+            interpolate_instruction(
+                &Instruction::Make {
+                    id: id.clone(),
+                    instance: "$ENV".into(),
+                    class: "Fixtures.$ENV".into(),
+                    args: vec!["$ENV".into(), "db-${ENV}".into()],
+                },
+                |name| symbols.get(name)
+            )
+        );
+        assert_eq!(
+            Ok(Instruction::CallAndAssign {
+                id: id.clone(),
+                symbol: "ENV".into(),
+                instance: "table".into(),
+                function: "set$ENV".into(),
+                args: vec!["Staging".into()],
+            }),
+            interpolate_instruction(
+                &Instruction::CallAndAssign {
+                    id: id.clone(),
+                    symbol: "ENV".into(),
+                    instance: "table".into(),
+                    function: "set$ENV".into(),
+                    args: vec!["$ENV".into()],
+                },
+                |name| symbols.get(name)
+            )
+        );
+        assert_eq!(
+            Err("ENV".into()),
+            interpolate_instruction(
+                &Instruction::Call {
+                    id,
+                    instance: "table".into(),
+                    function: "setA".into(),
+                    args: vec!["$ENV".into()],
+                },
+                |_| None
+            )
+        );
+    }
+}
@@ -2,10 +2,15 @@ use anyhow::{anyhow, bail, Result};
 use chrono::{NaiveDate, Utc};
 use convert_case::{Case, Casing};
 use markdown::{
-    mdast::{Node, TableCell},
+    mdast::{Node, Table, TableCell},
     unist::Position as MPosition,
 };
-use std::fmt::Display;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MarkdownCommand {
@@ -13,12 +18,71 @@ pub enum MarkdownCommand {
         path: String,
         position: Position,
     },
+    Library {
+        class: Class,
+        args: Vec<String>,
+    },
     DecisionTable {
         class: Class,
+        args: Vec<String>,
         r#type: DecisionTableType,
         table: Vec<TableRow>,
         snoozed: Snooze,
     },
+    ScriptTable {
+        class: Class,
+        args: Vec<String>,
+        table: Vec<ScriptRow>,
+        snoozed: Snooze,
+    },
+    QueryTable {
+        class: Class,
+        args: Vec<String>,
+        table: Vec<QueryRow>,
+        snoozed: Snooze,
+    },
+    OrderedQueryTable {
+        class: Class,
+        args: Vec<String>,
+        table: Vec<QueryRow>,
+        snoozed: Snooze,
+    },
+    SubsetQueryTable {
+        class: Class,
+        args: Vec<String>,
+        table: Vec<QueryRow>,
+        snoozed: Snooze,
+    },
+    Scenario {
+        name: MethodName,
+        parameters: Vec<String>,
+        table: Vec<ScriptRow>,
+    },
+    /// A decision table calling a scenario, each row on the instance of the actor class made for
+    /// the table.
+    ScenarioTable {
+        class: Class,
+        actor: Class,
+        args: Vec<String>,
+        table: Vec<ScriptRow>,
+        snoozed: Snooze,
+    },
+}
+
+impl MarkdownCommand {
+    pub fn class(&self) -> Option<&Class> {
+        match self {
+            MarkdownCommand::Import { .. }
+            | MarkdownCommand::Library { .. }
+            | MarkdownCommand::Scenario { .. } => None,
+            MarkdownCommand::DecisionTable { class, .. }
+            | MarkdownCommand::ScriptTable { class, .. }
+            | MarkdownCommand::QueryTable { class, .. }
+            | MarkdownCommand::OrderedQueryTable { class, .. }
+            | MarkdownCommand::SubsetQueryTable { class, .. }
+            | MarkdownCommand::ScenarioTable { class, .. } => Some(class),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,22 +95,305 @@ pub enum DecisionTableType {
 pub struct Position {
     line: usize,
     column: usize,
+    /// Where the scenario containing this position was called from.
+    call_site: Option<Box<Position>>,
+    /// The included document this position belongs to.
+    include: Option<Box<Include>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Include {
+    file: String,
+    position: Position,
 }
 
 impl Position {
     #[allow(dead_code)]
     pub fn new(line: usize, column: usize) -> Self {
-        Self { line, column }
+        Self {
+            line,
+            column,
+            call_site: None,
+            include: None,
+        }
     }
 
     pub fn line(&self) -> usize {
         self.line
     }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn called_from(mut self, call_site: &Position) -> Self {
+        self.call_site = Some(Box::new(call_site.clone()));
+        self
+    }
+
+    pub fn is_included(&self) -> bool {
+        self.include.is_some()
+    }
+
+    /// The position of the outermost scenario call this position comes from, or itself.
+    pub fn origin(&self) -> &Position {
+        let mut origin = self;
+        while let Some(call_site) = &origin.call_site {
+            origin = call_site;
+        }
+        origin
+    }
+
+    /// Displays the position with its file, following includes and scenario calls.
+    pub fn in_file<'a>(&'a self, file_path: &'a str) -> Location<'a> {
+        Location {
+            position: self,
+            file_path,
+        }
+    }
+}
+
+/// Marks every position of a command as part of a document included by another one.
+trait Included {
+    fn included(self, include: &Include) -> Self;
+}
+
+impl Included for Position {
+    /// A position already in an included document keeps its file and the include itself gets marked.
+    fn included(mut self, include: &Include) -> Self {
+        self.include = Some(Box::new(match self.include {
+            Some(inner) => Include {
+                file: inner.file,
+                position: inner.position.included(include),
+            },
+            None => include.clone(),
+        }));
+        self.call_site = self
+            .call_site
+            .map(|call_site| Box::new(call_site.included(include)));
+        self
+    }
+}
+
+impl<T: Included> Included for Vec<T> {
+    fn included(self, include: &Include) -> Self {
+        self.into_iter()
+            .map(|item| item.included(include))
+            .collect()
+    }
+}
+
+impl<A: Included, B: Included> Included for (A, B) {
+    fn included(self, include: &Include) -> Self {
+        (self.0.included(include), self.1.included(include))
+    }
+}
+
+impl Included for String {
+    fn included(self, _include: &Include) -> Self {
+        self
+    }
+}
+
+impl Included for Class {
+    fn included(self, include: &Include) -> Self {
+        Class(self.0, self.1.included(include))
+    }
+}
+
+impl Included for MethodName {
+    fn included(self, include: &Include) -> Self {
+        MethodName(self.0, self.1.included(include))
+    }
+}
+
+impl Included for Value {
+    fn included(self, include: &Include) -> Self {
+        Value(self.0, self.1.included(include))
+    }
+}
+
+impl Included for Column {
+    fn included(self, include: &Include) -> Self {
+        Column(self.0, self.1.included(include))
+    }
+}
+
+impl Included for TableRow {
+    fn included(self, include: &Include) -> Self {
+        TableRow {
+            position: self.position.included(include),
+            setters: self.setters.included(include),
+            getters: self.getters.included(include),
+        }
+    }
+}
+
+impl Included for ScriptRow {
+    fn included(self, include: &Include) -> Self {
+        ScriptRow {
+            position: self.position.included(include),
+            method: self.method.included(include),
+            args: self.args.included(include),
+            action: match self.action {
+                ScriptAction::Check(value) => ScriptAction::Check(value.included(include)),
+                ScriptAction::CheckNot(value) => ScriptAction::CheckNot(value.included(include)),
+                action => action,
+            },
+        }
+    }
+}
+
+impl Included for QueryRow {
+    fn included(self, include: &Include) -> Self {
+        QueryRow {
+            position: self.position.included(include),
+            cells: self.cells.included(include),
+        }
+    }
+}
+
+impl Included for MarkdownCommand {
+    fn included(self, include: &Include) -> Self {
+        match self {
+            MarkdownCommand::Import { path, position } => MarkdownCommand::Import {
+                path,
+                position: position.included(include),
+            },
+            MarkdownCommand::Library { class, args } => MarkdownCommand::Library {
+                class: class.included(include),
+                args,
+            },
+            MarkdownCommand::DecisionTable {
+                class,
+                args,
+                r#type,
+                table,
+                snoozed,
+            } => MarkdownCommand::DecisionTable {
+                class: class.included(include),
+                args,
+                r#type: match r#type {
+                    DecisionTableType::SingleMethod(method) => {
+                        DecisionTableType::SingleMethod(method.included(include))
+                    }
+                    r#type => r#type,
+                },
+                table: table.included(include),
+                snoozed,
+            },
+            MarkdownCommand::ScriptTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => MarkdownCommand::ScriptTable {
+                class: class.included(include),
+                args,
+                table: table.included(include),
+                snoozed,
+            },
+            MarkdownCommand::QueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => MarkdownCommand::QueryTable {
+                class: class.included(include),
+                args,
+                table: table.included(include),
+                snoozed,
+            },
+            MarkdownCommand::OrderedQueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => MarkdownCommand::OrderedQueryTable {
+                class: class.included(include),
+                args,
+                table: table.included(include),
+                snoozed,
+            },
+            MarkdownCommand::SubsetQueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => MarkdownCommand::SubsetQueryTable {
+                class: class.included(include),
+                args,
+                table: table.included(include),
+                snoozed,
+            },
+            MarkdownCommand::Scenario {
+                name,
+                parameters,
+                table,
+            } => MarkdownCommand::Scenario {
+                name: name.included(include),
+                parameters,
+                table: table.included(include),
+            },
+            MarkdownCommand::ScenarioTable {
+                class,
+                actor,
+                args,
+                table,
+                snoozed,
+            } => MarkdownCommand::ScenarioTable {
+                class: class.included(include),
+                actor: actor.included(include),
+                args,
+                table: table.included(include),
+                snoozed,
+            },
+        }
+    }
+}
+
+pub struct Location<'a> {
+    position: &'a Position,
+    file_path: &'a str,
+}
+
+impl Display for Location<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Location {
+            position,
+            file_path,
+        } = self;
+        match &position.include {
+            Some(include) => write!(
+                f,
+                "{}:{}:{} included at {}",
+                include.file,
+                position.line,
+                position.column,
+                include.position.in_file(file_path)
+            )?,
+            None => write!(f, "{file_path}:{}:{}", position.line, position.column)?,
+        }
+        if let Some(call_site) = &position.call_site {
+            write!(f, " via scenario call at {}", call_site.in_file(file_path))?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.line, self.column)
+        write!(f, "{}:{}", self.line, self.column)?;
+        let mut call_site = &self.call_site;
+        while let Some(position) = call_site {
+            write!(
+                f,
+                " via scenario call at {}:{}",
+                position.line, position.column
+            )?;
+            call_site = &position.call_site;
+        }
+        Ok(())
     }
 }
 
@@ -55,6 +402,8 @@ impl From<MPosition> for Position {
         Self {
             line: value.start.line,
             column: value.start.column,
+            call_site: None,
+            include: None,
         }
     }
 }
@@ -68,6 +417,9 @@ pub struct Class(pub String, pub Position);
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Value(pub String, pub Position);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column(pub String, pub Position);
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TableRow {
     pub position: Position,
@@ -75,6 +427,31 @@ pub struct TableRow {
     pub getters: Vec<(MethodName, Value)>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptRow {
+    pub position: Position,
+    pub method: MethodName,
+    pub args: Vec<Value>,
+    pub action: ScriptAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    Call,
+    Check(Value),
+    CheckNot(Value),
+    Ensure,
+    Reject,
+    Show,
+    SetSymbol(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryRow {
+    pub position: Position,
+    pub cells: Vec<(Column, Value)>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Snooze {
     date: Option<NaiveDate>,
@@ -113,126 +490,122 @@ enum MethodType {
     Commentary,
 }
 
+enum TableDirective {
+    Decision(Class),
+    Script(Class),
+    Query(Class),
+    OrderedQuery(Class),
+    SubsetQuery(Class),
+    Scenario(MethodName, Vec<String>),
+}
+
+/// What a markdown document shares with the documents it includes.
+#[derive(Default)]
+struct ParseContext {
+    include_chain: Vec<PathBuf>,
+    scenarios: HashMap<String, Vec<String>>,
+    /// The class and constructor arguments of the last script table, the actor of the scenarios
+    /// called as decision tables that don't name one.
+    actor: Option<(String, Vec<String>)>,
+}
+
+pub fn read_markdown(file_path: &Path) -> Result<Node> {
+    markdown::to_mdast(&read_to_string(file_path)?, &markdown::ParseOptions::gfm())
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))
+}
+
 pub fn get_commands_from_markdown(
     markdown: Node,
     file_path: impl AsRef<str>,
+) -> Result<Vec<MarkdownCommand>> {
+    let file_path = file_path.as_ref();
+    let mut context = ParseContext::default();
+    if let Ok(path) = Path::new(file_path).canonicalize() {
+        context.include_chain.push(path);
+    }
+    commands_from_markdown(markdown, file_path, &mut context)
+}
+
+fn commands_from_markdown(
+    markdown: Node,
+    file_path: &str,
+    context: &mut ParseContext,
 ) -> Result<Vec<MarkdownCommand>> {
     let mut result = Vec::new();
     match markdown {
         Node::Root(root) => {
-            let mut executing_test: Option<Class> = None;
+            let mut executing_test: Option<TableDirective> = None;
             for node in root.children {
-                if let Some(test_class) = executing_test {
+                if let Some(directive) = executing_test {
                     let Node::Table(table) = node else {
                         bail!(
                             "Expected a test table. {}",
-                            incorrect_node_error(&node, file_path.as_ref())
+                            incorrect_node_error(&node, file_path)
                         )
                     };
-                    let mut rows = Vec::new();
-                    let mut methods = Vec::new();
-                    for row in table.children {
-                        let Node::TableRow(row) = row else {
-                            bail!(
-                                "Expected a table row. {}",
-                                incorrect_node_error(&row, file_path.as_ref())
-                            )
-                        };
-                        if methods.is_empty() {
-                            for cell in row.children {
-                                let Node::TableCell(cell) = cell else {
-                                    bail!(
-                                        "Expected a table cell. {}",
-                                        incorrect_node_error(&cell, file_path.as_ref())
-                                    )
-                                };
-                                let (text, position) = get_text_and_position(cell)?;
-                                let text = text.trim();
-                                if text.starts_with('#') {
-                                    methods.push((
-                                        MethodName(text.into(), position),
-                                        MethodType::Commentary,
-                                    ));
-                                    continue;
-                                }
-                                match text.split_once('?') {
-                                    Some((getter_name, _)) => methods.push((
-                                        MethodName(getter_name.to_case(Case::Camel), position),
-                                        MethodType::Getter,
-                                    )),
-                                    _ => {
-                                        if text.starts_with("set") {
-                                            methods.push((
-                                                MethodName(text.to_case(Case::Camel), position),
-                                                MethodType::Setter,
-                                            ))
-                                        } else {
-                                            methods.push((
-                                                MethodName(
-                                                    format!("set {text}").to_case(Case::Camel),
-                                                    position,
-                                                ),
-                                                MethodType::Setter,
-                                            ))
-                                        }
-                                    }
-                                }
+                    result.push(match directive {
+                        TableDirective::Decision(class) => {
+                            let (name, _) = split_snooze(&class.0)?;
+                            let (name, _) = split_constructor_args(&name);
+                            match context.scenarios.get(&name.to_case(Case::Camel)) {
+                                Some(parameters) => scenario_table(
+                                    class,
+                                    parameters,
+                                    context.actor.as_ref(),
+                                    table,
+                                    file_path,
+                                )?,
+                                None => decision_table(class, table, file_path)?,
                             }
-                            continue;
                         }
-                        let mut table_row = TableRow {
-                            position: row.position.ok_or(anyhow!("Expected position"))?.into(),
-                            setters: Vec::new(),
-                            getters: Vec::new(),
-                        };
-                        for (i, n_cell) in row.children.into_iter().enumerate() {
-                            let Node::TableCell(cell) = n_cell else {
-                                bail!(
-                                    "Expected a table cell. {}",
-                                    incorrect_node_error(&n_cell, file_path.as_ref())
-                                )
-                            };
-                            let (text, position) = get_text_and_position(cell)?;
-                            let text = text.trim();
-                            match methods.get(i) {
-                                None => bail!(
-                                    "Wrong number of columns in row at {}:{}",
-                                    file_path.as_ref(),
-                                    position
-                                ),
-                                Some((method_name, MethodType::Getter)) => table_row
-                                    .getters
-                                    .push((method_name.clone(), Value(text.to_string(), position))),
-                                Some((method_name, MethodType::Setter)) => table_row
-                                    .setters
-                                    .push((method_name.clone(), Value(text.to_string(), position))),
-                                Some((_, MethodType::Commentary)) => {}
+                        TableDirective::Script(class) => {
+                            let script_table =
+                                script_table(class, table, &context.scenarios, file_path)?;
+                            if let MarkdownCommand::ScriptTable { class, args, .. } = &script_table
+                            {
+                                context.actor = Some((class.0.clone(), args.clone()));
                             }
+                            script_table
                         }
-                        rows.push(table_row);
-                    }
-                    let mut snoozed = Snooze::not_snooze();
-                    let mut stripped_test_class = test_class.0.clone();
-                    if let Some((class, rest)) = test_class.0.split_once(" -- ") {
-                        stripped_test_class = class.into();
-                        if let Some(date) = rest.trim().strip_prefix("snooze until") {
-                            snoozed =
-                                Snooze::snooze(NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?);
+                        TableDirective::Scenario(name, parameters) => {
+                            let table = script_rows(table, &context.scenarios, file_path)?;
+                            context.scenarios.insert(name.0.clone(), parameters.clone());
+                            MarkdownCommand::Scenario {
+                                name,
+                                parameters,
+                                table,
+                            }
+                        }
+                        TableDirective::Query(class) => {
+                            let (class, args, table, snoozed) =
+                                query_table(class, table, file_path)?;
+                            MarkdownCommand::QueryTable {
+                                class,
+                                args,
+                                table,
+                                snoozed,
+                            }
+                        }
+                        TableDirective::OrderedQuery(class) => {
+                            let (class, args, table, snoozed) =
+                                query_table(class, table, file_path)?;
+                            MarkdownCommand::OrderedQueryTable {
+                                class,
+                                args,
+                                table,
+                                snoozed,
+                            }
+                        }
+                        TableDirective::SubsetQuery(class) => {
+                            let (class, args, table, snoozed) =
+                                query_table(class, table, file_path)?;
+                            MarkdownCommand::SubsetQueryTable {
+                                class,
+                                args,
+                                table,
+                                snoozed,
+                            }
                         }
-                    }
-                    let mut r#type = DecisionTableType::MultipleSetterAndGetters;
-                    if let Some((class, rest)) = stripped_test_class.split_once('#') {
-                        r#type = DecisionTableType::SingleMethod(MethodName(
-                            rest.into(),
-                            test_class.1.clone(),
-                        ));
-                        stripped_test_class = class.into();
-                    }
-                    result.push(MarkdownCommand::DecisionTable {
-                        class: Class(stripped_test_class, test_class.1),
-                        r#type,
-                        table: rows,
-                        snoozed,
                     });
                     executing_test = None;
                     continue;
@@ -253,9 +626,58 @@ pub fn get_commands_from_markdown(
                                 path: import.to_string(),
                                 position,
                             }),
+                            Some(("include", include)) => result.extend(include_markdown(
+                                include.trim(),
+                                position,
+                                file_path,
+                                context,
+                            )?),
+                            Some(("library", library_class)) => {
+                                let (library_class, args) = split_constructor_args(library_class);
+                                result.push(MarkdownCommand::Library {
+                                    class: Class(library_class, position),
+                                    args,
+                                })
+                            }
                             Some(("decisionTable", test_class)) => {
-                                executing_test =
-                                    Some(Class(test_class.trim().to_string(), position));
+                                executing_test = Some(TableDirective::Decision(Class(
+                                    test_class.trim().to_string(),
+                                    position,
+                                )));
+                            }
+                            Some(("script", test_class)) => {
+                                executing_test = Some(TableDirective::Script(Class(
+                                    test_class.trim().to_string(),
+                                    position,
+                                )));
+                            }
+                            Some(("queryTable", test_class)) => {
+                                executing_test = Some(TableDirective::Query(Class(
+                                    test_class.trim().to_string(),
+                                    position,
+                                )));
+                            }
+                            Some(("orderedQueryTable", test_class)) => {
+                                executing_test = Some(TableDirective::OrderedQuery(Class(
+                                    test_class.trim().to_string(),
+                                    position,
+                                )));
+                            }
+                            Some(("subsetQueryTable", test_class)) => {
+                                executing_test = Some(TableDirective::SubsetQuery(Class(
+                                    test_class.trim().to_string(),
+                                    position,
+                                )));
+                            }
+                            Some(("scenario", signature)) => {
+                                let mut words = signature.split_whitespace();
+                                let Some(name) = words.next() else {
+                                    bail!("Expected a scenario name at {file_path}:{position}")
+                                };
+                                executing_test = Some(TableDirective::Scenario(
+                                    MethodName(name.to_case(Case::Camel), position),
+                                    words.map(str::to_string).collect(),
+                                ));
                             }
                             _ => continue,
                         }
@@ -263,11 +685,22 @@ pub fn get_commands_from_markdown(
                     _ => {}
                 }
             }
-            if let Some(class) = executing_test {
+            if let Some(directive) = executing_test {
+                let (table_type, class) = match directive {
+                    TableDirective::Decision(class) => ("decision", class),
+                    TableDirective::Script(class) => ("script", class),
+                    TableDirective::Query(class) => ("query", class),
+                    TableDirective::OrderedQuery(class) => ("ordered query", class),
+                    TableDirective::SubsetQuery(class) => ("subset query", class),
+                    TableDirective::Scenario(MethodName(name, position), _) => {
+                        ("scenario", Class(name, position))
+                    }
+                };
                 bail!(
-                    "Expected a decision table for {} at {}:{}:{} but got nothing",
+                    "Expected a {} table for {} at {}:{}:{} but got nothing",
+                    table_type,
                     class.0,
-                    file_path.as_ref(),
+                    file_path,
                     class.1.line,
                     class.1.column
                 )
@@ -278,6 +711,489 @@ pub fn get_commands_from_markdown(
     Ok(result)
 }
 
+/// Parses the commands of a document included by `file_path`, with the path relative to it.
+fn include_markdown(
+    include: &str,
+    position: Position,
+    file_path: &str,
+    context: &mut ParseContext,
+) -> Result<Vec<MarkdownCommand>> {
+    let included_file = Path::new(file_path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(include);
+    let canonical = included_file
+        .canonicalize()
+        .map_err(|err| anyhow!("Could not include {include} at {file_path}:{position}. {err}"))?;
+    if let Some(index) = context
+        .include_chain
+        .iter()
+        .position(|path| *path == canonical)
+    {
+        bail!(
+            "Include cycle {} -> {} at {file_path}:{position}",
+            context.include_chain[index..]
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> "),
+            canonical.display()
+        )
+    }
+    let included_file = included_file.display().to_string();
+    let markdown = read_markdown(&canonical)?;
+    context.include_chain.push(canonical);
+    let commands = commands_from_markdown(markdown, &included_file, context)
+        .map_err(|err| anyhow!("{err}\nIncluded at {file_path}:{position}"))?;
+    context.include_chain.pop();
+    let include = Include {
+        file: included_file,
+        position,
+    };
+    Ok(commands
+        .into_iter()
+        .map(|command| command.included(&include))
+        .collect())
+}
+
+fn decision_table(test_class: Class, table: Table, file_path: &str) -> Result<MarkdownCommand> {
+    let mut rows = Vec::new();
+    let mut methods = Vec::new();
+    for row in table.children {
+        let Node::TableRow(row) = row else {
+            bail!(
+                "Expected a table row. {}",
+                incorrect_node_error(&row, file_path)
+            )
+        };
+        if methods.is_empty() {
+            for cell in row.children {
+                let Node::TableCell(cell) = cell else {
+                    bail!(
+                        "Expected a table cell. {}",
+                        incorrect_node_error(&cell, file_path)
+                    )
+                };
+                let (text, position) = get_text_and_position(cell)?;
+                let text = text.trim();
+                if text.starts_with('#') {
+                    methods.push((MethodName(text.into(), position), MethodType::Commentary));
+                    continue;
+                }
+                match text.split_once('?') {
+                    Some((getter_name, _)) => methods.push((
+                        MethodName(getter_name.to_case(Case::Camel), position),
+                        MethodType::Getter,
+                    )),
+                    _ => {
+                        if text.starts_with("set") {
+                            methods.push((
+                                MethodName(text.to_case(Case::Camel), position),
+                                MethodType::Setter,
+                            ))
+                        } else {
+                            methods.push((
+                                MethodName(format!("set {text}").to_case(Case::Camel), position),
+                                MethodType::Setter,
+                            ))
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+        let mut table_row = TableRow {
+            position: row.position.ok_or(anyhow!("Expected position"))?.into(),
+            setters: Vec::new(),
+            getters: Vec::new(),
+        };
+        for (i, n_cell) in row.children.into_iter().enumerate() {
+            let Node::TableCell(cell) = n_cell else {
+                bail!(
+                    "Expected a table cell. {}",
+                    incorrect_node_error(&n_cell, file_path)
+                )
+            };
+            let (text, position) = get_text_and_position(cell)?;
+            let text = text.trim();
+            match methods.get(i) {
+                None => bail!(
+                    "Wrong number of columns in row at {}:{}",
+                    file_path,
+                    position
+                ),
+                Some((method_name, MethodType::Getter)) => table_row
+                    .getters
+                    .push((method_name.clone(), Value(text.to_string(), position))),
+                Some((method_name, MethodType::Setter)) => table_row
+                    .setters
+                    .push((method_name.clone(), Value(text.to_string(), position))),
+                Some((_, MethodType::Commentary)) => {}
+            }
+        }
+        rows.push(table_row);
+    }
+    let (stripped_test_class, snoozed) = split_snooze(&test_class.0)?;
+    let (mut stripped_test_class, args) = split_constructor_args(&stripped_test_class);
+    let mut r#type = DecisionTableType::MultipleSetterAndGetters;
+    if let Some((class, rest)) = stripped_test_class.split_once('#') {
+        r#type = DecisionTableType::SingleMethod(MethodName(rest.into(), test_class.1.clone()));
+        stripped_test_class = class.into();
+    }
+    Ok(MarkdownCommand::DecisionTable {
+        class: Class(stripped_test_class, test_class.1),
+        args,
+        r#type,
+        table: rows,
+        snoozed,
+    })
+}
+
+fn script_table(
+    test_class: Class,
+    table: Table,
+    scenarios: &HashMap<String, Vec<String>>,
+    file_path: &str,
+) -> Result<MarkdownCommand> {
+    let (stripped_test_class, snoozed) = split_snooze(&test_class.0)?;
+    let (stripped_test_class, args) = split_constructor_args(&stripped_test_class);
+    Ok(MarkdownCommand::ScriptTable {
+        class: Class(stripped_test_class, test_class.1),
+        args,
+        table: script_rows(table, scenarios, file_path)?,
+        snoozed,
+    })
+}
+
+fn script_rows(
+    table: Table,
+    scenarios: &HashMap<String, Vec<String>>,
+    file_path: &str,
+) -> Result<Vec<ScriptRow>> {
+    let mut rows = Vec::new();
+    // The header row only documents the columns, every other row is an action.
+    for row in table.children.into_iter().skip(1) {
+        let Node::TableRow(row) = row else {
+            bail!(
+                "Expected a table row. {}",
+                incorrect_node_error(&row, file_path)
+            )
+        };
+        let position: Position = row.position.ok_or(anyhow!("Expected position"))?.into();
+        let mut cells = row_cells(row.children, file_path)?;
+        while cells.last().is_some_and(|(text, _)| text.is_empty()) {
+            cells.pop();
+        }
+        if let Some(row) = script_row(position, cells, scenarios, file_path)? {
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+/// Every row of a table calling a scenario becomes a call with the arguments
+/// in the order of the scenario parameters, whatever the order of the columns.
+/// The actor class, with its constructor arguments, can follow the scenario name,
+/// `sumIs Calculator`. Otherwise it is the class of the last script table.
+fn scenario_table(
+    test_class: Class,
+    parameters: &[String],
+    last_actor: Option<&(String, Vec<String>)>,
+    table: Table,
+    file_path: &str,
+) -> Result<MarkdownCommand> {
+    let (stripped_test_class, snoozed) = split_snooze(&test_class.0)?;
+    let (scenario_name, actor) = split_constructor_args(&stripped_test_class);
+    let name = MethodName(scenario_name.to_case(Case::Camel), test_class.1.clone());
+    let mut rows = Vec::new();
+    let mut columns: Option<Vec<Column>> = None;
+    for row in table.children {
+        let Node::TableRow(row) = row else {
+            bail!(
+                "Expected a table row. {}",
+                incorrect_node_error(&row, file_path)
+            )
+        };
+        let Some(columns) = &columns else {
+            let header = row_cells(row.children, file_path)?;
+            for (parameter, position) in header.iter() {
+                if !parameter.starts_with('#') && !parameters.contains(parameter) {
+                    bail!(
+                        "Unknown parameter {parameter} for scenario {} at {file_path}:{position}",
+                        name.0
+                    )
+                }
+            }
+            if let Some(parameter) = parameters
+                .iter()
+                .find(|parameter| !header.iter().any(|(column, _)| column == *parameter))
+            {
+                bail!(
+                    "Missing parameter {parameter} for scenario {} at {file_path}:{}",
+                    name.0,
+                    test_class.1
+                )
+            }
+            columns = Some(
+                header
+                    .into_iter()
+                    .map(|(text, position)| Column(text, position))
+                    .collect(),
+            );
+            continue;
+        };
+        let position: Position = row.position.ok_or(anyhow!("Expected position"))?.into();
+        let cells = row_cells(row.children, file_path)?;
+        if cells.len() > columns.len() {
+            bail!("Wrong number of columns in row at {file_path}:{position}")
+        }
+        let args = parameters
+            .iter()
+            .map(|parameter| {
+                let index = columns
+                    .iter()
+                    .position(|column| &column.0 == parameter)
+                    .expect("Checked the header has every parameter");
+                cells
+                    .get(index)
+                    .map(|(text, position)| Value(text.clone(), position.clone()))
+                    .unwrap_or_else(|| Value(String::new(), position.clone()))
+            })
+            .collect();
+        rows.push(ScriptRow {
+            position,
+            method: name.clone(),
+            args,
+            action: ScriptAction::Call,
+        });
+    }
+    let (actor, args) = if let Some((class, args)) = actor.split_first() {
+        (class.clone(), args.to_vec())
+    } else if let Some(last_actor) = last_actor {
+        last_actor.clone()
+    } else {
+        bail!(
+            "Scenario {} at {file_path}:{} needs an actor class after its name or a script table before it",
+            name.0,
+            test_class.1
+        )
+    };
+    Ok(MarkdownCommand::ScenarioTable {
+        class: Class(stripped_test_class, test_class.1.clone()),
+        actor: Class(actor, test_class.1),
+        args,
+        table: rows,
+        snoozed,
+    })
+}
+
+fn query_table(
+    test_class: Class,
+    table: Table,
+    file_path: &str,
+) -> Result<(Class, Vec<String>, Vec<QueryRow>, Snooze)> {
+    let mut rows = Vec::new();
+    let mut columns: Option<Vec<Column>> = None;
+    for row in table.children {
+        let Node::TableRow(row) = row else {
+            bail!(
+                "Expected a table row. {}",
+                incorrect_node_error(&row, file_path)
+            )
+        };
+        let Some(columns) = &columns else {
+            columns = Some(
+                row_cells(row.children, file_path)?
+                    .into_iter()
+                    .map(|(text, position)| Column(text, position))
+                    .collect(),
+            );
+            continue;
+        };
+        let mut query_row = QueryRow {
+            position: row.position.ok_or(anyhow!("Expected position"))?.into(),
+            cells: Vec::new(),
+        };
+        for (i, (text, position)) in row_cells(row.children, file_path)?.into_iter().enumerate() {
+            match columns.get(i) {
+                None => bail!(
+                    "Wrong number of columns in row at {}:{}",
+                    file_path,
+                    position
+                ),
+                Some(column) if column.0.starts_with('#') => {}
+                // Blank cells are not compared against the returned rows.
+                Some(_) if text.is_empty() => {}
+                Some(column) => query_row
+                    .cells
+                    .push((column.clone(), Value(text, position))),
+            }
+        }
+        rows.push(query_row);
+    }
+    let (stripped_test_class, snoozed) = split_snooze(&test_class.0)?;
+    let (stripped_test_class, args) = split_constructor_args(&stripped_test_class);
+    Ok((
+        Class(stripped_test_class, test_class.1),
+        args,
+        rows,
+        snoozed,
+    ))
+}
+
+fn script_row(
+    position: Position,
+    mut cells: Vec<(String, Position)>,
+    scenarios: &HashMap<String, Vec<String>>,
+    file_path: &str,
+) -> Result<Option<ScriptRow>> {
+    let Some((first, _)) = cells.first().cloned() else {
+        return Ok(None);
+    };
+    let action = match first.as_str() {
+        "note" => return Ok(None),
+        comment if comment.starts_with('#') => return Ok(None),
+        "check" | "check not" => {
+            if cells.len() < 3 {
+                bail!("Expected a method and a value to {first} in row at {file_path}:{position}")
+            }
+            let (value, value_position) = cells.pop().expect("Checked the number of cells");
+            let value = Value(value, value_position);
+            cells.remove(0);
+            if first == "check" {
+                ScriptAction::Check(value)
+            } else {
+                ScriptAction::CheckNot(value)
+            }
+        }
+        "ensure" | "reject" | "show" => {
+            if cells.len() < 2 {
+                bail!("Expected a method to {first} in row at {file_path}:{position}")
+            }
+            cells.remove(0);
+            match first.as_str() {
+                "ensure" => ScriptAction::Ensure,
+                "reject" => ScriptAction::Reject,
+                _ => ScriptAction::Show,
+            }
+        }
+        symbol if symbol.starts_with('$') && symbol.ends_with('=') && symbol.len() > 2 => {
+            if cells.len() < 2 {
+                bail!("Expected a method to assign {symbol} in row at {file_path}:{position}")
+            }
+            cells.remove(0);
+            ScriptAction::SetSymbol(symbol[1..symbol.len() - 1].to_string())
+        }
+        _ => ScriptAction::Call,
+    };
+    let mut cells = cells.into_iter();
+    let (name, method_position) = cells.next().expect("Checked the number of cells");
+    let scenario = name.to_case(Case::Camel);
+    // Scenarios take every other cell as an argument, in the order of their parameters.
+    let (method, args) = if scenarios.contains_key(&scenario) {
+        (
+            MethodName(scenario, method_position),
+            cells
+                .map(|(value, position)| Value(value, position))
+                .collect(),
+        )
+    } else {
+        script_method((name, method_position), cells)
+    };
+    Ok(Some(ScriptRow {
+        position,
+        method,
+        args,
+        action,
+    }))
+}
+
+/// Builds the method name and arguments of a script row the same way FitNesse does.
+/// Cells alternate between parts of the method name and arguments, unless the first cell
+/// ends with `;`, in which case it is the whole method name and every other cell is an argument.
+fn script_method(
+    (name, position): (String, Position),
+    cells: impl Iterator<Item = (String, Position)>,
+) -> (MethodName, Vec<Value>) {
+    if let Some(name) = name.strip_suffix(';') {
+        return (
+            MethodName(name.to_case(Case::Camel), position),
+            cells
+                .map(|(value, position)| Value(value, position))
+                .collect(),
+        );
+    }
+    let mut name_parts = vec![name];
+    let mut args = Vec::new();
+    for (i, (text, position)) in cells.enumerate() {
+        if i % 2 == 0 {
+            args.push(Value(text, position));
+        } else {
+            name_parts.push(text);
+        }
+    }
+    (
+        MethodName(name_parts.join(" ").to_case(Case::Camel), position),
+        args,
+    )
+}
+
+fn split_snooze(test_class: &str) -> Result<(String, Snooze)> {
+    let Some((class, rest)) = test_class.split_once(" -- ") else {
+        return Ok((test_class.into(), Snooze::not_snooze()));
+    };
+    let mut snoozed = Snooze::not_snooze();
+    if let Some(date) = rest.trim().strip_prefix("snooze until") {
+        snoozed = Snooze::snooze(NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")?);
+    }
+    Ok((class.into(), snoozed))
+}
+
+/// Splits the fixture class from its constructor arguments. They can follow the class
+/// separated by spaces, `Fixture arg1 arg2`, or between parentheses, `Fixture(arg1, arg2)`.
+/// Anything after the closing parenthesis stays with the class, like a `#method` suffix.
+fn split_constructor_args(test_class: &str) -> (String, Vec<String>) {
+    let test_class = test_class.trim();
+    match test_class.find(['(', ' ']) {
+        Some(index) if test_class[index..].starts_with('(') => {
+            let (args, rest) = test_class[index + 1..]
+                .split_once(')')
+                .unwrap_or((&test_class[index + 1..], ""));
+            (
+                format!("{}{}", &test_class[..index], rest.trim()),
+                args.split(',')
+                    .map(|arg| arg.trim().to_string())
+                    .filter(|arg| !arg.is_empty())
+                    .collect(),
+            )
+        }
+        Some(index) => (
+            test_class[..index].to_string(),
+            test_class[index..]
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        ),
+        None => (test_class.to_string(), Vec::new()),
+    }
+}
+
+fn row_cells(cells: Vec<Node>, file_path: &str) -> Result<Vec<(String, Position)>> {
+    cells
+        .into_iter()
+        .map(|cell| {
+            let Node::TableCell(cell) = cell else {
+                bail!(
+                    "Expected a table cell. {}",
+                    incorrect_node_error(&cell, file_path)
+                )
+            };
+            let (text, position) = get_text_and_position(cell)?;
+            Ok((text.trim().to_string(), position))
+        })
+        .collect()
+}
+
 fn get_text_and_position(cell: TableCell) -> Result<(String, Position)> {
     let position = cell.position.ok_or(anyhow!("Expected position"))?.into();
     if cell.children.is_empty() {
@@ -329,6 +1245,11 @@ fn node_position_to_error(position: Option<&MPosition>) -> String {
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::{
+        env::temp_dir,
+        fs::{create_dir_all, write},
+    };
+    use ulid::Ulid;
 
     #[test]
     fn calculator() -> Result<()> {
@@ -358,6 +1279,7 @@ This is a calculator example
                     position: Position::new(6, 1)
                 },
                 MarkdownCommand::DecisionTable {
+                    args: vec![],
                     class: Class("Calculator".into(), Position::new(8, 1)),
                     r#type: DecisionTableType::MultipleSetterAndGetters,
                     table: vec![
@@ -420,6 +1342,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![TableRow {
@@ -472,6 +1395,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![TableRow {
@@ -508,6 +1432,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::SingleMethod(MethodName(
                     "log".into(),
@@ -547,6 +1472,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![TableRow {
@@ -583,6 +1509,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::SingleMethod(MethodName(
                     "log".into(),
@@ -629,6 +1556,7 @@ This is a calculator example
         )?;
         assert_eq!(
             vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Table".into(), Position::new(2, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![
@@ -754,6 +1682,536 @@ Another node
         Ok(())
     }
 
+    #[test]
+    fn script_table() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (script Login -- snooze until 2023-11-20)
+
+| action        | method | value    |     |       |
+|---------------|--------|----------|-----|-------|
+| login with    | bob    | password | xyz |       |
+| note          | ignore |          |     |       |
+| # comment     |        |          |     |       |
+| check         | user   | bob      |     |       |
+| check not     | user   | alice    |     |       |
+| ensure        | logged |          |     |       |
+| reject        | admin  |          |     |       |
+| show          | name   |          |     |       |
+| $V=           | user   |          |     |       |
+| login;        | bob    | xyz      |     |       |
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![MarkdownCommand::ScriptTable {
+                args: vec![],
+                class: Class("Login".into(), Position::new(2, 1)),
+                table: vec![
+                    ScriptRow {
+                        position: Position::new(6, 1),
+                        method: MethodName("loginWithPassword".into(), Position::new(6, 3)),
+                        args: vec![
+                            Value("bob".into(), Position::new(6, 19)),
+                            Value("xyz".into(), Position::new(6, 39)),
+                        ],
+                        action: ScriptAction::Call,
+                    },
+                    ScriptRow {
+                        position: Position::new(9, 1),
+                        method: MethodName("user".into(), Position::new(9, 19)),
+                        args: vec![],
+                        action: ScriptAction::Check(Value("bob".into(), Position::new(9, 28))),
+                    },
+                    ScriptRow {
+                        position: Position::new(10, 1),
+                        method: MethodName("user".into(), Position::new(10, 19)),
+                        args: vec![],
+                        action: ScriptAction::CheckNot(Value(
+                            "alice".into(),
+                            Position::new(10, 28)
+                        )),
+                    },
+                    ScriptRow {
+                        position: Position::new(11, 1),
+                        method: MethodName("logged".into(), Position::new(11, 19)),
+                        args: vec![],
+                        action: ScriptAction::Ensure,
+                    },
+                    ScriptRow {
+                        position: Position::new(12, 1),
+                        method: MethodName("admin".into(), Position::new(12, 19)),
+                        args: vec![],
+                        action: ScriptAction::Reject,
+                    },
+                    ScriptRow {
+                        position: Position::new(13, 1),
+                        method: MethodName("name".into(), Position::new(13, 19)),
+                        args: vec![],
+                        action: ScriptAction::Show,
+                    },
+                    ScriptRow {
+                        position: Position::new(14, 1),
+                        method: MethodName("user".into(), Position::new(14, 19)),
+                        args: vec![],
+                        action: ScriptAction::SetSymbol("V".into()),
+                    },
+                    ScriptRow {
+                        position: Position::new(15, 1),
+                        method: MethodName("login".into(), Position::new(15, 3)),
+                        args: vec![
+                            Value("bob".into(), Position::new(15, 19)),
+                            Value("xyz".into(), Position::new(15, 28)),
+                        ],
+                        action: ScriptAction::Call,
+                    },
+                ],
+                snoozed: Snooze::snooze(NaiveDate::from_ymd_opt(2023, 11, 20).unwrap()),
+            }],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn query_table() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (queryTable Users)
+
+| name  | # comment | age |
+|-------|-----------|-----|
+| bob   | ignored   | 30  |
+| alice |           |     |
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![MarkdownCommand::QueryTable {
+                args: vec![],
+                class: Class("Users".into(), Position::new(2, 1)),
+                table: vec![
+                    QueryRow {
+                        position: Position::new(6, 1),
+                        cells: vec![
+                            (
+                                Column("name".into(), Position::new(4, 3)),
+                                Value("bob".into(), Position::new(6, 3))
+                            ),
+                            (
+                                Column("age".into(), Position::new(4, 23)),
+                                Value("30".into(), Position::new(6, 23))
+                            ),
+                        ],
+                    },
+                    QueryRow {
+                        position: Position::new(7, 1),
+                        cells: vec![(
+                            Column("name".into(), Position::new(4, 3)),
+                            Value("alice".into(), Position::new(7, 3))
+                        )],
+                    },
+                ],
+                snoozed: Snooze::not_snooze(),
+            }],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_and_subset_query_tables() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (orderedQueryTable Users)
+
+| name |
+|------|
+| bob  |
+
+[//]: # (subsetQueryTable Users -- snooze until 2023-11-20)
+
+| name  |
+|-------|
+| alice |
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![
+                MarkdownCommand::OrderedQueryTable {
+                    args: vec![],
+                    class: Class("Users".into(), Position::new(2, 1)),
+                    table: vec![QueryRow {
+                        position: Position::new(6, 1),
+                        cells: vec![(
+                            Column("name".into(), Position::new(4, 3)),
+                            Value("bob".into(), Position::new(6, 3))
+                        )],
+                    }],
+                    snoozed: Snooze::not_snooze(),
+                },
+                MarkdownCommand::SubsetQueryTable {
+                    args: vec![],
+                    class: Class("Users".into(), Position::new(8, 1)),
+                    table: vec![QueryRow {
+                        position: Position::new(12, 1),
+                        cells: vec![(
+                            Column("name".into(), Position::new(10, 3)),
+                            Value("alice".into(), Position::new(12, 3))
+                        )],
+                    }],
+                    snoozed: Snooze::snooze(NaiveDate::from_ymd_opt(2023, 11, 20).unwrap()),
+                },
+            ],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_script_check_without_value() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (script Login)
+
+| action | method |
+|--------|--------|
+| check  | user   |
+            "#,
+            ),
+            "test_file.md",
+        );
+        assert_eq!(
+            "Expected a method and a value to check in row at test_file.md:6:1",
+            commands.unwrap_err().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_expected_a_script_table_got_nothing() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (script Login)
+
+            "#,
+            ),
+            "test_file.md",
+        );
+        assert_eq!(
+            "Expected a script table for Login at test_file.md:2:1 but got nothing",
+            commands.unwrap_err().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn constructor_args() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # "decisionTable Calculator(1, $V)#sum -- snooze until 2023-11-20"
+
+| a |
+|---|
+
+[//]: # (script Fixture tenant_1 $CONFIG)
+
+| action |
+|--------|
+
+[//]: # "queryTable Query()"
+
+| name |
+|------|
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![
+                MarkdownCommand::DecisionTable {
+                    class: Class("Calculator".into(), Position::new(2, 1)),
+                    args: vec!["1".into(), "$V".into()],
+                    r#type: DecisionTableType::SingleMethod(MethodName(
+                        "sum".into(),
+                        Position::new(2, 1)
+                    )),
+                    table: vec![],
+                    snoozed: Snooze::snooze(NaiveDate::from_ymd_opt(2023, 11, 20).unwrap()),
+                },
+                MarkdownCommand::ScriptTable {
+                    class: Class("Fixture".into(), Position::new(7, 1)),
+                    args: vec!["tenant_1".into(), "$CONFIG".into()],
+                    table: vec![],
+                    snoozed: Snooze::not_snooze(),
+                },
+                MarkdownCommand::QueryTable {
+                    class: Class("Query".into(), Position::new(12, 1)),
+                    args: vec![],
+                    table: vec![],
+                    snoozed: Snooze::not_snooze(),
+                },
+            ],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn library() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (library Fixtures.TimeTravel)
+
+[//]: # (library Fixtures.Database main)
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![
+                MarkdownCommand::Library {
+                    class: Class("Fixtures.TimeTravel".into(), Position::new(2, 1)),
+                    args: vec![],
+                },
+                MarkdownCommand::Library {
+                    class: Class("Fixtures.Database".into(), Position::new(4, 1)),
+                    args: vec!["main".into()],
+                },
+            ],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn include() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("common"))?;
+        write(
+            dir.join("common/setup.md"),
+            "[//]: # (include imports.md)\n\n[//]: # (script Setup)\n\n| action |\n|--------|\n| reset  |\n",
+        )?;
+        write(dir.join("common/imports.md"), "[//]: # (import Fixtures)\n")?;
+        let main = dir.join("main.md");
+        write(&main, "# Main\n\n[//]: # (include common/setup.md)\n")?;
+        let main = main.display().to_string();
+        let commands = get_commands_from_markdown(read_markdown(Path::new(&main))?, &main)?;
+        let [MarkdownCommand::Import { path, position }, MarkdownCommand::ScriptTable { class, table, .. }] =
+            &commands[..]
+        else {
+            bail!("Expected an import and a script table, got {commands:?}");
+        };
+        assert_eq!("Fixtures", path);
+        assert_eq!(
+            format!(
+                "{0}/common/imports.md:1:1 included at {0}/common/setup.md:1:1 included at {0}/main.md:3:1",
+                dir.display()
+            ),
+            position.in_file(&main).to_string()
+        );
+        assert_eq!("Setup", class.0);
+        assert_eq!(
+            format!(
+                "{0}/common/setup.md:7:1 included at {0}/main.md:3:1",
+                dir.display()
+            ),
+            table[0].position.in_file(&main).to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_include_cycle() -> Result<()> {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(&dir)?;
+        write(dir.join("a.md"), "[//]: # (include b.md)\n")?;
+        write(dir.join("b.md"), "[//]: # (include a.md)\n")?;
+        let a = dir.join("a.md");
+        let result = get_commands_from_markdown(read_markdown(&a)?, a.display().to_string());
+        let canonical = dir.canonicalize()?;
+        assert_eq!(
+            format!(
+                "Include cycle {0}/a.md -> {0}/b.md -> {0}/a.md at {1}/b.md:1:1\nIncluded at {1}/a.md:1:1",
+                canonical.display(),
+                dir.display()
+            ),
+            result.unwrap_err().to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scenario() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (scenario login user password)
+
+| action        | value     |
+|---------------|-----------|
+| enter user    | @user     |
+| ensure        | logged in |
+
+[//]: # (script Fixture)
+
+| action | user | password |
+|--------|------|----------|
+| login  | bob  | secret   |
+
+[//]: # (decisionTable login)
+
+| password | user  | # note |
+|----------|-------|--------|
+| pass     | alice | admin  |
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        assert_eq!(
+            vec![
+                MarkdownCommand::Scenario {
+                    name: MethodName("login".into(), Position::new(2, 1)),
+                    parameters: vec!["user".into(), "password".into()],
+                    table: vec![
+                        ScriptRow {
+                            position: Position::new(6, 1),
+                            method: MethodName("enterUser".into(), Position::new(6, 3)),
+                            args: vec![Value("@user".into(), Position::new(6, 19))],
+                            action: ScriptAction::Call,
+                        },
+                        ScriptRow {
+                            position: Position::new(7, 1),
+                            method: MethodName("loggedIn".into(), Position::new(7, 19)),
+                            args: vec![],
+                            action: ScriptAction::Ensure,
+                        },
+                    ],
+                },
+                MarkdownCommand::ScriptTable {
+                    args: vec![],
+                    class: Class("Fixture".into(), Position::new(9, 1)),
+                    table: vec![ScriptRow {
+                        position: Position::new(13, 1),
+                        method: MethodName("login".into(), Position::new(13, 3)),
+                        args: vec![
+                            Value("bob".into(), Position::new(13, 12)),
+                            Value("secret".into(), Position::new(13, 19)),
+                        ],
+                        action: ScriptAction::Call,
+                    }],
+                    snoozed: Snooze::not_snooze(),
+                },
+                MarkdownCommand::ScenarioTable {
+                    class: Class("login".into(), Position::new(15, 1)),
+                    actor: Class("Fixture".into(), Position::new(15, 1)),
+                    args: vec![],
+                    table: vec![ScriptRow {
+                        position: Position::new(19, 1),
+                        method: MethodName("login".into(), Position::new(15, 1)),
+                        args: vec![
+                            Value("alice".into(), Position::new(19, 14)),
+                            Value("pass".into(), Position::new(19, 3)),
+                        ],
+                        action: ScriptAction::Call,
+                    }],
+                    snoozed: Snooze::not_snooze(),
+                },
+            ],
+            commands
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_scenario_table_missing_parameter() {
+        let result = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (scenario login user password)
+
+| action |
+|--------|
+
+[//]: # (decisionTable login)
+
+| user |
+|------|
+| bob  |
+            "#,
+            ),
+            "test_file.md",
+        );
+        assert_eq!(
+            "Missing parameter password for scenario login at test_file.md:7:1",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn scenario_table_naming_its_actor() -> Result<()> {
+        let commands = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (scenario login user)
+
+| action     | value |
+|------------|-------|
+| enter user | @user |
+
+[//]: # (decisionTable login Fixture arg)
+
+| user |
+|------|
+| bob  |
+            "#,
+            ),
+            "test_file.md",
+        )?;
+        let MarkdownCommand::ScenarioTable { actor, args, .. } = &commands[1] else {
+            bail!("Expected a scenario table, got {:?}", commands[1]);
+        };
+        assert_eq!(&Class("Fixture".into(), Position::new(8, 1)), actor);
+        assert_eq!(&vec!["arg".to_string()], args);
+        Ok(())
+    }
+
+    #[test]
+    fn error_scenario_table_without_actor() {
+        let result = get_commands_from_markdown(
+            parse_markdown(
+                r#"
+[//]: # (scenario login user)
+
+| action |
+|--------|
+
+[//]: # (decisionTable login)
+
+| user |
+|------|
+| bob  |
+            "#,
+            ),
+            "test_file.md",
+        );
+        assert_eq!(
+            "Scenario login at test_file.md:7:1 needs an actor class after its name or a script table before it",
+            result.unwrap_err().to_string()
+        );
+    }
+
     fn parse_markdown(markdown: &str) -> Node {
         markdown::to_mdast(markdown, &markdown::ParseOptions::gfm())
             .expect("Error parsing markdown")
@@ -1,28 +1,33 @@
 use self::{
-    markdown_commands::{get_commands_from_markdown, Snooze},
-    slim_instructions_from_commands::ExpectedResulWithSnooze,
+    markdown_commands::{get_commands_from_markdown, read_markdown},
+    slim_instructions_from_commands::{ExpectedResulWithSnooze, TestCase},
 };
 use crate::processor::markdown_commands::MarkdownCommand;
 use crate::processor::{
+    interpolate::interpolate_instruction,
     slim_instructions_from_commands::get_instructions_from_commands,
     validate_result::validate_result,
 };
-use anyhow::{anyhow, Result};
-use markdown::mdast::Node;
+use anyhow::{bail, Result};
 use regex::Regex;
-use slim_protocol::{Instruction, SlimConnection};
+use slim_protocol::{ExceptionMessage, Id, Instruction, InstructionResultValue, SlimConnection};
 use std::collections::HashMap;
+use std::iter::Peekable;
 use std::{
-    fs::read_to_string,
     io::{Read, Write},
     path::Path,
 };
 
+pub use markdown_commands::{Position, Snooze};
+pub use slim_instructions_from_commands::{ExpectedResult, ExpectedResultValue};
+pub use validate_result::{CheckedResult, Failure};
+
+mod interpolate;
 mod markdown_commands;
 mod slim_instructions_from_commands;
 mod validate_result;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct State {
     symbols: HashMap<String, String>,
 }
@@ -35,6 +40,84 @@ impl State {
     pub fn get_symbol(&self, key: &str) -> Option<&String> {
         self.symbols.get(key)
     }
+
+    /// Forgets the symbol whose call failed to assign it, so its previous value is not used.
+    pub fn remove_symbol(&mut self, key: &str) {
+        self.symbols.remove(key);
+    }
+}
+
+/// The instructions of a markdown file, with the results expected for them and how those are
+/// grouped into test cases.
+pub type MarkdownInstructions = (
+    Vec<Instruction>,
+    Vec<ExpectedResulWithSnooze>,
+    Vec<TestCase>,
+);
+
+/// The checked results of a test case, with the file and position of the command it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub file: String,
+    pub position: Position,
+    pub results: Vec<CheckedResult>,
+    /// Why the test case was not executed, like a previous one stopping the test.
+    pub skipped: Option<String>,
+}
+
+/// A fixture asking to stop testing, with its reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Abort {
+    /// The remaining tables of the document are skipped.
+    Test(String),
+    /// No other document is tested either.
+    Suite(String),
+}
+
+impl TestCaseResult {
+    pub fn location(&self) -> String {
+        self.position.in_file(&self.file).to_string()
+    }
+
+    pub fn failures(&self) -> Vec<(String, Snooze)> {
+        self.results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .failures
+                    .iter()
+                    .map(|failure| (failure.message.clone(), result.snooze.clone()))
+            })
+            .collect()
+    }
+
+    /// If any of the failures is not snoozed.
+    pub fn failed(&self) -> bool {
+        self.results
+            .iter()
+            .any(|result| !result.passed() && !result.snooze.should_snooze())
+    }
+
+    /// If a fixture asked to stop the test or the suite while executing the test case.
+    pub fn abort(&self) -> Option<Abort> {
+        self.results.iter().find_map(|result| match &result.actual {
+            InstructionResultValue::Exception(message) => {
+                let reason = message
+                    .pretty_message()
+                    .unwrap_or(message.raw_message())
+                    .to_string();
+                if message.is_stop_suite() {
+                    Some(Abort::Suite(reason))
+                } else if message.is_stop_test() {
+                    Some(Abort::Test(reason))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -69,112 +152,372 @@ impl Filter {
             .into_iter()
             .filter(|command| {
                 self.filters.iter().all(|filter| match filter {
-                    FilterType::FixtureClass(regex) => match command {
-                        MarkdownCommand::DecisionTable { class, .. } => regex.is_match(&class.0),
-                        MarkdownCommand::Import { .. } => true,
-                    },
-                    FilterType::Line(line) => match command {
-                        MarkdownCommand::DecisionTable { class, .. } => class.1.line() == *line,
-                        MarkdownCommand::Import { .. } => true,
-                    },
+                    FilterType::FixtureClass(regex) => command
+                        .class()
+                        .map(|class| regex.is_match(&class.0))
+                        .unwrap_or(true),
+                    // Included documents are setup for the tables selected by line.
+                    FilterType::Line(line) => command
+                        .class()
+                        .map(|class| class.1.is_included() || class.1.line() == *line)
+                        .unwrap_or(true),
                 })
             })
             .collect()
     }
 }
 
-pub fn process_markdown_into_instructions(
-    file_path: impl AsRef<Path>,
+pub fn markdown_into_instructions(
+    file_path: &Path,
     filter: &Filter,
-) -> Result<(Vec<Instruction>, Vec<ExpectedResulWithSnooze>)> {
-    let file_path = file_path.as_ref();
-    let file_path_display = file_path.display().to_string();
-    print!("Testing file {}...", file_path_display);
-    let markdown = parse_markdown(file_path)?;
-    let commands = filter.apply(get_commands_from_markdown(markdown, file_path_display)?);
-    if !commands
-        .iter()
-        .any(|command| matches!(command, MarkdownCommand::DecisionTable { .. }))
-    {
-        return Ok((vec![], vec![]));
+) -> Result<MarkdownInstructions> {
+    let markdown = read_markdown(file_path)?;
+    let commands = filter.apply(get_commands_from_markdown(
+        markdown,
+        file_path.display().to_string(),
+    )?);
+    if !commands.iter().any(|command| command.class().is_some()) {
+        return Ok((vec![], vec![], vec![]));
     }
     get_instructions_from_commands(commands)
 }
 
-pub fn execute_instructions_and_print_result<R: Read, W: Write>(
+/// The instructions of a test case with the results expected for them, sent to the slim server
+/// on their own so the results of each table arrive as soon as it is executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCaseBatch {
+    pub test_case: TestCase,
+    instructions: Vec<Instruction>,
+    expected_result: Vec<ExpectedResulWithSnooze>,
+}
+
+/// Splits the instructions of a file into one batch per test case.
+pub fn test_case_batches(
+    (instructions, expected_result, test_cases): MarkdownInstructions,
+) -> Vec<TestCaseBatch> {
+    let mut instructions = instructions.into_iter();
+    let mut expected_result = expected_result.into_iter();
+    test_cases
+        .into_iter()
+        .map(|test_case| TestCaseBatch {
+            instructions: instructions
+                .by_ref()
+                .take(test_case.expected_results)
+                .collect(),
+            expected_result: expected_result
+                .by_ref()
+                .take(test_case.expected_results)
+                .collect(),
+            test_case,
+        })
+        .collect()
+}
+
+/// Executes the instructions of the test case, replacing the symbols of their class names and
+/// arguments with the values of the state.
+pub fn execute_test_case<R: Read, W: Write>(
     connection: &mut SlimConnection<R, W>,
     file_path: &str,
+    batch: TestCaseBatch,
+    state: &mut State,
+) -> Result<TestCaseResult> {
+    let mut pending = batch
+        .instructions
+        .into_iter()
+        .zip(batch.expected_result)
+        .peekable();
+    let mut results = Vec::new();
+    while pending.peek().is_some() {
+        let Interpolated {
+            instructions,
+            mut expected_result,
+            undefined,
+        } = take_interpolated(&mut pending, state);
+        let result = if instructions.is_empty() {
+            Vec::new()
+        } else {
+            connection.send_instructions(&instructions)?
+        };
+        // The slim server doesn't execute anything after a fixture stops the test.
+        let stopped = result.last().is_some_and(|result| match &result.value {
+            InstructionResultValue::Exception(message) => {
+                message.is_stop_test() || message.is_stop_suite()
+            }
+            _ => false,
+        });
+        if stopped {
+            expected_result.truncate(result.len());
+        }
+        if expected_result.len() != result.len() {
+            bail!("Number of instruction results `{}` does not matched the expected number of results `{}`", result.len(), expected_result.len())
+        }
+        let mut checked = validate_result(file_path, expected_result, result, state)?.into_iter();
+        let mut undefined = undefined.into_iter().peekable();
+        // The instructions left out are reported at their place, up to the one that stopped.
+        for index in 0.. {
+            if stopped && checked.len() == 0 {
+                break;
+            }
+            if let Some((_, symbol, expected)) = undefined.next_if(|(at, ..)| *at == index) {
+                results.push(undefined_symbol(file_path, &symbol, expected));
+            } else if let Some(checked) = checked.next() {
+                results.push(checked);
+            } else {
+                break;
+            }
+        }
+        if stopped {
+            break;
+        }
+    }
+    Ok(TestCaseResult {
+        name: batch.test_case.name,
+        file: file_path.to_string(),
+        position: batch.test_case.position,
+        results,
+        skipped: None,
+    })
+}
+
+/// The next pending instructions to send together, and the ones left out as they use a symbol
+/// without a value, with their place among all of them.
+struct Interpolated {
     instructions: Vec<Instruction>,
     expected_result: Vec<ExpectedResulWithSnooze>,
-    show_snoozed: bool,
-    state: &mut State,
-) -> Result<bool> {
-    let result = connection.send_instructions(&instructions)?;
-    let failures = validate_result(file_path, expected_result, result, state)?;
-    print_fail_or_ok(show_snoozed, failures)
+    undefined: Vec<(usize, String, ExpectedResulWithSnooze)>,
 }
 
-fn parse_markdown(file_path: &Path) -> Result<Node> {
-    markdown::to_mdast(&read_to_string(file_path)?, &markdown::ParseOptions::gfm())
-        .map_err(|err| anyhow!("Error parsing markdown {err}"))
+/// Takes the pending instructions that can be sent together, with their symbols interpolated. It
+/// only stops before one using a symbol assigned by a previous one, whose value is known once the
+/// results of those arrive. One using an undefined symbol is left out without stopping.
+fn take_interpolated(
+    pending: &mut Peekable<impl Iterator<Item = (Instruction, ExpectedResulWithSnooze)>>,
+    state: &State,
+) -> Interpolated {
+    let mut interpolated = Interpolated {
+        instructions: Vec::new(),
+        expected_result: Vec::new(),
+        undefined: Vec::new(),
+    };
+    let mut assigned: Vec<String> = Vec::new();
+    while let Some((instruction, _)) = pending.peek() {
+        let symbol = |name: &str| {
+            state
+                .get_symbol(name)
+                .filter(|_| !assigned.iter().any(|symbol| symbol == name))
+        };
+        let instruction = match interpolate_instruction(instruction, symbol) {
+            Ok(instruction) => instruction,
+            Err(symbol) if assigned.contains(&symbol) => break,
+            Err(symbol) => {
+                let (_, expected) = pending.next().expect("An instruction was peeked");
+                let index = interpolated.instructions.len() + interpolated.undefined.len();
+                interpolated.undefined.push((index, symbol, expected));
+                continue;
+            }
+        };
+        if let Instruction::CallAndAssign { symbol, .. } = &instruction {
+            assigned.push(symbol.clone());
+        }
+        interpolated.instructions.push(instruction);
+        interpolated
+            .expected_result
+            .extend(pending.next().map(|(_, expected)| expected));
+    }
+    interpolated
+}
+
+/// A failure at the instruction that was not sent, as it uses a symbol without a value.
+fn undefined_symbol(
+    file_path: &str,
+    symbol: &str,
+    (expected, snooze): ExpectedResulWithSnooze,
+) -> CheckedResult {
+    let message = format!(
+        "Undefined symbol `{symbol}` in {}",
+        expected.position.in_file(file_path)
+    );
+    CheckedResult {
+        actual: InstructionResultValue::Exception(ExceptionMessage::new(message.clone())),
+        snooze,
+        failures: vec![Failure {
+            position: expected.position.clone(),
+            message,
+        }],
+        expected,
+    }
 }
 
-fn print_fail_or_ok(show_snoozed: bool, failures: Vec<(String, Snooze)>) -> Result<bool> {
+/// Assigns the symbols of the state in the slim server, so a new connection can use them.
+pub fn assign_symbols<R: Read, W: Write>(
+    connection: &mut SlimConnection<R, W>,
+    state: &State,
+) -> Result<()> {
+    if state.symbols.is_empty() {
+        return Ok(());
+    }
+    let instructions: Vec<Instruction> = state
+        .symbols
+        .iter()
+        .map(|(symbol, value)| Instruction::Assign {
+            id: Id::new(),
+            symbol: symbol.clone(),
+            value: value.clone(),
+        })
+        .collect();
+    for (instruction, result) in instructions
+        .iter()
+        .zip(connection.send_instructions(&instructions)?)
+    {
+        if result.value != InstructionResultValue::Ok {
+            bail!("Failed to assign {instruction:?}. Got {}", result.value)
+        }
+    }
+    Ok(())
+}
+
+/// Makes an instance of the fixture, so a slim server reused across files can clear its state in
+/// the constructor.
+pub fn reset_fixture<R: Read, W: Write>(
+    connection: &mut SlimConnection<R, W>,
+    class: &str,
+) -> Result<()> {
+    let instruction = Instruction::Make {
+        id: Id::new(),
+        instance: "temoc_reset".into(),
+        class: class.into(),
+        args: vec![],
+    };
+    for result in connection.send_instructions(&[instruction])? {
+        if result.value != InstructionResultValue::Ok {
+            bail!(
+                "Failed to reset the fixture `{class}`. Got {}",
+                result.value
+            )
+        }
+    }
+    Ok(())
+}
+
+/// A failure at the test case whose batch of instructions timed out.
+pub fn timed_out_test_case(file_path: &str, test_case: &TestCase, message: &str) -> TestCaseResult {
+    TestCaseResult {
+        name: test_case.name.clone(),
+        file: file_path.to_string(),
+        position: test_case.position.clone(),
+        results: vec![CheckedResult {
+            expected: ExpectedResult::ok(Id::new(), test_case.position.clone()),
+            actual: InstructionResultValue::Exception(ExceptionMessage::new(message.to_string())),
+            snooze: Snooze::not_snooze(),
+            failures: vec![Failure {
+                position: test_case.position.clone(),
+                message: format!("{message} in {}", test_case.position.in_file(file_path)),
+            }],
+        }],
+        skipped: None,
+    }
+}
+
+/// A test case that is not executed, with the reason why.
+pub fn skipped_test_case(file_path: &str, test_case: &TestCase, reason: &str) -> TestCaseResult {
+    TestCaseResult {
+        name: test_case.name.clone(),
+        file: file_path.to_string(),
+        position: test_case.position.clone(),
+        results: vec![],
+        skipped: Some(reason.to_string()),
+    }
+}
+
+pub fn print_fail_or_ok(
+    out: &mut impl Write,
+    show_snoozed: bool,
+    results: &[TestCaseResult],
+) -> Result<bool> {
+    let failures: Vec<(String, Snooze)> =
+        results.iter().flat_map(TestCaseResult::failures).collect();
     if !failures.is_empty() {
         if failures.iter().any(|(_, snoose)| snoose.should_snooze()) {
-            println!("SNOOZED");
+            writeln!(out, "SNOOZED")?;
         } else {
-            println!("FAIL");
+            writeln!(out, "FAIL")?;
         }
         let mut fail = false;
         for (failure, snooze) in failures.into_iter() {
             let should_snooze = snooze.should_snooze();
             if should_snooze && show_snoozed {
                 let snooze_string = format!(" -- snoozed until {}", &snooze);
-                println!(
+                writeln!(
+                    out,
                     "{failure}{}",
                     if should_snooze { &snooze_string } else { "" }
-                );
+                )?;
             } else if !should_snooze {
-                println!("{failure}");
+                writeln!(out, "{failure}")?;
             }
 
             fail |= !should_snooze;
         }
+        print_skipped(out, results)?;
         return Ok(fail);
     }
-    println!("OK");
+    if results.iter().any(|result| result.skipped.is_some()) {
+        writeln!(out, "SKIPPED")?;
+        print_skipped(out, results)?;
+        return Ok(false);
+    }
+    writeln!(out, "OK")?;
     Ok(false)
 }
 
+fn print_skipped(out: &mut impl Write, results: &[TestCaseResult]) -> Result<()> {
+    for result in results {
+        if let Some(reason) = &result.skipped {
+            writeln!(
+                out,
+                "Skipped {} in {}: {reason}",
+                result.name,
+                result.location()
+            )?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::processor::markdown_commands::{Class, DecisionTableType, Position};
+    use anyhow::anyhow;
+    use slim_protocol::InstructionResult;
 
     #[test]
     fn test_filter() -> Result<()> {
         let filter = Filter::new().fixture_class("Calculator")?;
         let commands = vec![
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(1, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
                 snoozed: Snooze::not_snooze(),
             },
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(10, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
                 snoozed: Snooze::not_snooze(),
             },
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator2".into(), Position::new(11, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
                 snoozed: Snooze::not_snooze(),
             },
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("AnotherFixture".into(), Position::new(11, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
@@ -189,18 +532,21 @@ mod test {
         assert_eq!(
             vec![
                 MarkdownCommand::DecisionTable {
+                    args: vec![],
                     class: Class("Calculator".into(), Position::new(1, 1)),
                     r#type: DecisionTableType::MultipleSetterAndGetters,
                     table: vec![],
                     snoozed: Snooze::not_snooze(),
                 },
                 MarkdownCommand::DecisionTable {
+                    args: vec![],
                     class: Class("Calculator".into(), Position::new(10, 1)),
                     r#type: DecisionTableType::MultipleSetterAndGetters,
                     table: vec![],
                     snoozed: Snooze::not_snooze(),
                 },
                 MarkdownCommand::DecisionTable {
+                    args: vec![],
                     class: Class("Calculator2".into(), Position::new(11, 1)),
                     r#type: DecisionTableType::MultipleSetterAndGetters,
                     table: vec![],
@@ -221,6 +567,7 @@ mod test {
         let filter = Filter::new();
         let commands = vec![
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(1, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
@@ -240,12 +587,14 @@ mod test {
         let filter = Filter::new().line(10);
         let commands = vec![
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(1, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
                 snoozed: Snooze::not_snooze(),
             },
             MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Calculator".into(), Position::new(10, 1)),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![],
@@ -260,6 +609,7 @@ mod test {
         assert_eq!(
             vec![
                 MarkdownCommand::DecisionTable {
+                    args: vec![],
                     class: Class("Calculator".into(), Position::new(10, 1)),
                     r#type: DecisionTableType::MultipleSetterAndGetters,
                     table: vec![],
@@ -274,4 +624,179 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn split_instructions_per_test_case() -> Result<()> {
+        let markdown = markdown::to_mdast(
+            r#"[//]: # "import Fixtures"
+[//]: # "decisionTable Calculator"
+
+| a | b | sum? |
+| - | - | ---- |
+| 1 | 2 | 3    |
+"#,
+            &markdown::ParseOptions::gfm(),
+        )
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))?;
+        let batches = test_case_batches(get_instructions_from_commands(
+            get_commands_from_markdown(markdown, "calculator.md")?,
+        )?);
+        assert_eq!(
+            vec![
+                ("import Fixtures".to_string(), 1),
+                ("Calculator".to_string(), 8),
+            ],
+            batches
+                .iter()
+                .map(|batch| (batch.test_case.name.clone(), batch.instructions.len()))
+                .collect::<Vec<_>>()
+        );
+        assert!(batches
+            .iter()
+            .all(|batch| batch.instructions.len() == batch.expected_result.len()));
+        Ok(())
+    }
+
+    #[test]
+    fn interpolate_until_a_symbol_assigned_in_the_batch() -> Result<()> {
+        let markdown = markdown::to_mdast(
+            r#"[//]: # "decisionTable Calculator"
+
+| a             | b    | sum? |
+| ------------- | ---- | ---- |
+| $BASE         | 2    | $V=  |
+| user-${V}@x   | $V   | 5    |
+"#,
+            &markdown::ParseOptions::gfm(),
+        )
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))?;
+        let batch = test_case_batches(get_instructions_from_commands(get_commands_from_markdown(
+            markdown,
+            "calculator.md",
+        )?)?)
+        .remove(0);
+        let mut state = State::default();
+        state.set_symbol("BASE".into(), "10".into());
+        let mut pending = batch
+            .instructions
+            .into_iter()
+            .zip(batch.expected_result)
+            .peekable();
+        let setter_args = |instructions: &[Instruction]| -> Vec<String> {
+            instructions
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Instruction::Call { function, args, .. } if function.starts_with("set") => {
+                        Some(args[0].clone())
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let interpolated = take_interpolated(&mut pending, &state);
+        assert_eq!(vec!["10", "2"], setter_args(&interpolated.instructions));
+        assert_eq!(
+            interpolated.instructions.len(),
+            interpolated.expected_result.len()
+        );
+        assert!(interpolated.undefined.is_empty());
+        state.set_symbol("V".into(), "12".into());
+        let interpolated = take_interpolated(&mut pending, &state);
+        assert_eq!(
+            vec!["user-12@x", "12"],
+            setter_args(&interpolated.instructions)
+        );
+        assert!(pending.peek().is_none());
+
+        let call = |arg: &str, line: usize| {
+            (
+                Instruction::Call {
+                    id: Id::new(),
+                    instance: "table".into(),
+                    function: "setA".into(),
+                    args: vec![arg.into()],
+                },
+                (
+                    ExpectedResult::ok(Id::new(), Position::new(line, 3)),
+                    Snooze::not_snooze(),
+                ),
+            )
+        };
+        let mut pending = vec![call("1", 4), call("$MISSING", 5), call("2", 6)]
+            .into_iter()
+            .peekable();
+        // The instruction using an undefined symbol is left out without splitting the others.
+        let mut interpolated = take_interpolated(&mut pending, &state);
+        assert_eq!(vec!["1", "2"], setter_args(&interpolated.instructions));
+        assert_eq!(1, interpolated.undefined.len());
+        let (index, symbol, expected) = interpolated.undefined.remove(0);
+        assert_eq!(1, index);
+        assert_eq!("MISSING", symbol);
+        let checked = undefined_symbol("calculator.md", &symbol, expected);
+        assert_eq!(
+            vec![Failure {
+                position: Position::new(5, 3),
+                message: "Undefined symbol `MISSING` in calculator.md:5:3".into(),
+            }],
+            checked.failures
+        );
+        assert!(pending.peek().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn skip_the_instructions_using_a_symbol_whose_assignment_threw() -> Result<()> {
+        let markdown = markdown::to_mdast(
+            r#"[//]: # "decisionTable Calculator"
+
+| a  | b    | sum? |
+| -- | ---- | ---- |
+| 1  | 2    | $V=  |
+| $V | 3    | 5    |
+"#,
+            &markdown::ParseOptions::gfm(),
+        )
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))?;
+        let batch = test_case_batches(get_instructions_from_commands(get_commands_from_markdown(
+            markdown,
+            "calculator.md",
+        )?)?)
+        .remove(0);
+        let mut state = State::default();
+        state.set_symbol("V".into(), "stale".into());
+        let mut pending = batch
+            .instructions
+            .into_iter()
+            .zip(batch.expected_result)
+            .peekable();
+
+        let Interpolated {
+            instructions,
+            expected_result,
+            ..
+        } = take_interpolated(&mut pending, &state);
+        assert_eq!(instructions.len(), expected_result.len());
+        let results = expected_result
+            .iter()
+            .map(|(expected, _)| match expected.value {
+                ExpectedResultValue::SetSymbol(_) => InstructionResult::exception(
+                    expected.id.clone(),
+                    ExceptionMessage::new("Overflow".into()),
+                ),
+                _ => InstructionResult::ok(expected.id.clone()),
+            })
+            .collect();
+        validate_result("calculator.md", expected_result, results, &mut state)?;
+        assert_eq!(None, state.get_symbol("V"));
+
+        let mut interpolated = take_interpolated(&mut pending, &state);
+        let (index, symbol, (expected, _)) = interpolated.undefined.remove(0);
+        assert_eq!(0, index);
+        assert_eq!("V", symbol);
+        assert_eq!(Position::new(6, 3), expected.position);
+        // The rest of the row is still sent.
+        assert!(!interpolated.instructions.is_empty());
+        Ok(())
+    }
 }
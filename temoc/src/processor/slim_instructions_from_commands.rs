@@ -1,21 +1,41 @@
-use anyhow::Result;
-use std::fmt::Display;
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::{collections::HashMap, fmt::Display};
 use ulid::Ulid;
 
 use slim_protocol::{Id, Instruction};
 
+use super::interpolate::{cell_symbol, unescape};
 use super::markdown_commands::{
-    Class, DecisionTableType, MarkdownCommand, MethodName, Position, Snooze, Value,
+    Class, DecisionTableType, MarkdownCommand, MethodName, Position, QueryRow, ScriptAction,
+    ScriptRow, Snooze, Value,
 };
 
 pub type ExpectedResulWithSnooze = (ExpectedResult, Snooze);
 
+/// A command of the markdown reported as a single test, like a table, and the number of
+/// consecutive expected results that belong to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub position: Position,
+    pub expected_results: usize,
+}
+
 pub fn get_instructions_from_commands(
     commands: Vec<MarkdownCommand>,
-) -> Result<(Vec<Instruction>, Vec<ExpectedResulWithSnooze>)> {
+) -> Result<(
+    Vec<Instruction>,
+    Vec<ExpectedResulWithSnooze>,
+    Vec<TestCase>,
+)> {
     let mut instructions = Vec::new();
     let mut expected_result = Vec::new();
+    let mut test_cases = Vec::new();
+    let mut scenarios = HashMap::new();
     for command in commands {
+        let test_case = test_case_name_and_position(&command);
+        let first_expected_result = expected_result.len();
         match command {
             MarkdownCommand::Import { path, position } => {
                 let id = Id::new();
@@ -25,8 +45,22 @@ pub fn get_instructions_from_commands(
                 });
                 expected_result.push((ExpectedResult::ok(id, position), Snooze::not_snooze()))
             }
+            MarkdownCommand::Library {
+                class: Class(library_class, position),
+                args,
+            } => {
+                let id = Id::new();
+                instructions.push(Instruction::Make {
+                    id: id.clone(),
+                    instance: format!("library{}", Ulid::new()),
+                    class: library_class,
+                    args,
+                });
+                expected_result.push((ExpectedResult::ok(id, position), Snooze::not_snooze()))
+            }
             MarkdownCommand::DecisionTable {
                 class: Class(test_class, position),
+                args,
                 r#type,
                 table,
                 snoozed,
@@ -37,7 +71,7 @@ pub fn get_instructions_from_commands(
                     id: id.clone(),
                     instance: table_instance.clone(),
                     class: test_class,
-                    args: Vec::new(),
+                    args,
                 });
                 expected_result.push((ExpectedResult::ok(id, position.clone()), snoozed.clone()));
                 let id = Id::new();
@@ -100,7 +134,7 @@ pub fn get_instructions_from_commands(
                             for (getter_name, Value(value, position)) in row.getters.into_iter() {
                                 let id = Id::new();
                                 if let Some(value) =
-                                    value.strip_prefix("$").and_then(|v| v.strip_suffix("="))
+                                    cell_symbol(&value).and_then(|v| v.strip_suffix("="))
                                 {
                                     instructions.push(Instruction::CallAndAssign {
                                         id: id.clone(),
@@ -118,7 +152,7 @@ pub fn get_instructions_from_commands(
                                         ),
                                         snoozed.clone(),
                                     ));
-                                } else if let Some(value) = value.strip_prefix("$") {
+                                } else if let Some(value) = cell_symbol(&value) {
                                     instructions.push(Instruction::Call {
                                         id: id.clone(),
                                         instance: table_instance.clone(),
@@ -142,7 +176,7 @@ pub fn get_instructions_from_commands(
                                         args: vec![],
                                     });
                                     expected_result.push((
-                                        ExpectedResult::string(id, position, getter_name, value),
+                                        ExpectedResult::cell(id, position, getter_name, value),
                                         snoozed.clone(),
                                     ));
                                 };
@@ -154,7 +188,7 @@ pub fn get_instructions_from_commands(
                             let result = row
                                 .getters
                                 .into_iter()
-                                .map(|getter| ExpectedResultValue::String(getter.1 .0))
+                                .map(|getter| ExpectedResultValue::cell(getter.1 .0))
                                 .collect();
                             let id = Id::new();
                             instructions.push(Instruction::Call {
@@ -182,12 +216,142 @@ pub fn get_instructions_from_commands(
                     snoozed,
                 ));
             }
+            MarkdownCommand::ScriptTable {
+                class: Class(test_class, position),
+                args,
+                table,
+                snoozed,
+            } => {
+                let table_instance = Ulid::new().to_string();
+                let id = Id::new();
+                instructions.push(Instruction::Make {
+                    id: id.clone(),
+                    instance: table_instance.clone(),
+                    class: test_class,
+                    args,
+                });
+                expected_result.push((ExpectedResult::ok(id, position), snoozed.clone()));
+                for row in table.into_iter() {
+                    script_row_instructions(
+                        &table_instance,
+                        row,
+                        &snoozed,
+                        &scenarios,
+                        &mut Vec::new(),
+                        &mut instructions,
+                        &mut expected_result,
+                    )?;
+                }
+            }
+            MarkdownCommand::Scenario {
+                name,
+                parameters,
+                table,
+            } => {
+                scenarios.insert(name.0, Scenario { parameters, table });
+            }
+            MarkdownCommand::ScenarioTable {
+                class: _,
+                actor: Class(actor_class, position),
+                args,
+                table,
+                snoozed,
+            } => {
+                // The scenario runs against its own actor, whichever tables run before it.
+                let table_instance = Ulid::new().to_string();
+                let id = Id::new();
+                instructions.push(Instruction::Make {
+                    id: id.clone(),
+                    instance: table_instance.clone(),
+                    class: actor_class,
+                    args,
+                });
+                expected_result.push((ExpectedResult::ok(id, position), snoozed.clone()));
+                for row in table.into_iter() {
+                    script_row_instructions(
+                        &table_instance,
+                        row,
+                        &snoozed,
+                        &scenarios,
+                        &mut Vec::new(),
+                        &mut instructions,
+                        &mut expected_result,
+                    )?;
+                }
+            }
+            MarkdownCommand::QueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => query_table_instructions(
+                class,
+                args,
+                table,
+                snoozed,
+                ExpectedResult::table,
+                &mut instructions,
+                &mut expected_result,
+            ),
+            MarkdownCommand::OrderedQueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => query_table_instructions(
+                class,
+                args,
+                table,
+                snoozed,
+                ExpectedResult::ordered_table,
+                &mut instructions,
+                &mut expected_result,
+            ),
+            MarkdownCommand::SubsetQueryTable {
+                class,
+                args,
+                table,
+                snoozed,
+            } => query_table_instructions(
+                class,
+                args,
+                table,
+                snoozed,
+                ExpectedResult::subset_table,
+                &mut instructions,
+                &mut expected_result,
+            ),
+        }
+        if let Some((name, position)) = test_case {
+            if expected_result.len() > first_expected_result {
+                test_cases.push(TestCase {
+                    name,
+                    position,
+                    expected_results: expected_result.len() - first_expected_result,
+                });
+            }
         }
     }
-    Ok((instructions, expected_result))
+    Ok((instructions, expected_result, test_cases))
 }
 
-#[derive(PartialEq, Eq, Debug)]
+fn test_case_name_and_position(command: &MarkdownCommand) -> Option<(String, Position)> {
+    match command {
+        MarkdownCommand::Import { path, position } => {
+            Some((format!("import {path}"), position.clone()))
+        }
+        MarkdownCommand::Library {
+            class: Class(class, position),
+            ..
+        } => Some((format!("library {class}"), position.clone())),
+        MarkdownCommand::Scenario { .. } => None,
+        command => command
+            .class()
+            .map(|Class(class, position)| (class.clone(), position.clone())),
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ExpectedResult {
     pub id: Id,
     pub position: Position,
@@ -246,6 +410,34 @@ impl ExpectedResult {
         }
     }
 
+    /// The value of a cell, that can be an expression like `error` or `< 5`.
+    pub fn cell(id: Id, position: Position, method_name: MethodName, value: String) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::cell(value),
+        }
+    }
+
+    pub fn not_string(id: Id, position: Position, method_name: MethodName, value: String) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::NotString(value),
+        }
+    }
+
+    pub fn no_exception(id: Id, position: Position, method_name: MethodName) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::NoException,
+        }
+    }
+
     pub fn set_symbol(id: Id, position: Position, method_name: MethodName, value: String) -> Self {
         Self {
             id,
@@ -277,9 +469,248 @@ impl ExpectedResult {
             value: ExpectedResultValue::List(value),
         }
     }
+
+    pub fn table(
+        id: Id,
+        position: Position,
+        method_name: MethodName,
+        rows: Vec<ExpectedRow>,
+    ) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::Table(rows),
+        }
+    }
+
+    pub fn ordered_table(
+        id: Id,
+        position: Position,
+        method_name: MethodName,
+        rows: Vec<ExpectedRow>,
+    ) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::OrderedTable(rows),
+        }
+    }
+
+    pub fn subset_table(
+        id: Id,
+        position: Position,
+        method_name: MethodName,
+        rows: Vec<ExpectedRow>,
+    ) -> Self {
+        Self {
+            id,
+            position,
+            method_name: Some(method_name),
+            value: ExpectedResultValue::SubsetTable(rows),
+        }
+    }
+}
+
+struct Scenario {
+    parameters: Vec<String>,
+    table: Vec<ScriptRow>,
+}
+
+fn script_row_instructions(
+    table_instance: &str,
+    ScriptRow {
+        position,
+        method,
+        args,
+        action,
+    }: ScriptRow,
+    snoozed: &Snooze,
+    scenarios: &HashMap<String, Scenario>,
+    calling: &mut Vec<String>,
+    instructions: &mut Vec<Instruction>,
+    expected_result: &mut Vec<ExpectedResulWithSnooze>,
+) -> Result<()> {
+    if let Some(scenario) = scenarios.get(&method.0) {
+        if action != ScriptAction::Call {
+            bail!(
+                "Expected a plain call to scenario {} at {}",
+                method.0,
+                position
+            )
+        }
+        if calling.contains(&method.0) {
+            bail!("Scenario {} calls itself at {}", method.0, position)
+        }
+        if args.len() != scenario.parameters.len() {
+            bail!(
+                "Scenario {} expects {} arguments but got {} at {}",
+                method.0,
+                scenario.parameters.len(),
+                args.len(),
+                position
+            )
+        }
+        let mut arguments: Vec<(&String, String)> = scenario
+            .parameters
+            .iter()
+            .zip(args.into_iter().map(|arg| arg.0))
+            .collect();
+        // Replace the longest parameters first so `@user` does not clobber `@username`.
+        arguments.sort_by_key(|(parameter, _)| std::cmp::Reverse(parameter.len()));
+        let substitute = |Value(value, value_position): Value| {
+            Value(
+                substitute_parameters(value, &arguments),
+                value_position.called_from(&position),
+            )
+        };
+        calling.push(method.0.clone());
+        for row in scenario.table.iter().cloned() {
+            let row = ScriptRow {
+                position: row.position.called_from(&position),
+                method: MethodName(row.method.0, row.method.1.called_from(&position)),
+                args: row.args.into_iter().map(substitute).collect(),
+                action: match row.action {
+                    ScriptAction::Check(value) => ScriptAction::Check(substitute(value)),
+                    ScriptAction::CheckNot(value) => ScriptAction::CheckNot(substitute(value)),
+                    action => action,
+                },
+            };
+            script_row_instructions(
+                table_instance,
+                row,
+                snoozed,
+                scenarios,
+                calling,
+                instructions,
+                expected_result,
+            )?;
+        }
+        calling.pop();
+        return Ok(());
+    }
+    let id = Id::new();
+    let args = args.into_iter().map(|arg| arg.0).collect();
+    if let ScriptAction::SetSymbol(symbol) = action {
+        instructions.push(Instruction::CallAndAssign {
+            id: id.clone(),
+            instance: table_instance.to_string(),
+            function: method.0.clone(),
+            symbol: symbol.clone(),
+            args,
+        });
+        expected_result.push((
+            ExpectedResult::set_symbol(id, position, method, symbol),
+            snoozed.clone(),
+        ));
+        return Ok(());
+    }
+    instructions.push(Instruction::Call {
+        id: id.clone(),
+        instance: table_instance.to_string(),
+        function: method.0.clone(),
+        args,
+    });
+    let expected = match action {
+        ScriptAction::Call => ExpectedResult::not_string(id, position, method, "false".into()),
+        ScriptAction::Check(Value(value, position)) => match cell_symbol(&value) {
+            Some(symbol) => ExpectedResult::symbol(id, position, method, symbol.into()),
+            None => ExpectedResult::cell(id, position, method, value),
+        },
+        ScriptAction::CheckNot(Value(value, position)) => {
+            ExpectedResult::not_string(id, position, method, unescape(&value))
+        }
+        ScriptAction::Ensure => ExpectedResult::string(id, position, method, "true".into()),
+        ScriptAction::Reject => ExpectedResult::string(id, position, method, "false".into()),
+        ScriptAction::Show => ExpectedResult::no_exception(id, position, method),
+        ScriptAction::SetSymbol(_) => unreachable!("Handled above"),
+    };
+    expected_result.push((expected, snoozed.clone()));
+    Ok(())
+}
+
+/// Replaces `@parameter` and `@{parameter}` in a scenario cell with the call argument.
+fn substitute_parameters(value: String, arguments: &[(&String, String)]) -> String {
+    arguments
+        .iter()
+        .fold(value, |value, (parameter, argument)| {
+            value
+                .replace(&format!("@{{{parameter}}}"), argument)
+                .replace(&format!("@{parameter}"), argument)
+        })
+}
+
+fn query_table_instructions(
+    Class(test_class, position): Class,
+    args: Vec<String>,
+    table: Vec<QueryRow>,
+    snoozed: Snooze,
+    expected_table: fn(Id, Position, MethodName, Vec<ExpectedRow>) -> ExpectedResult,
+    instructions: &mut Vec<Instruction>,
+    expected_result: &mut Vec<ExpectedResulWithSnooze>,
+) {
+    let table_instance = Ulid::new().to_string();
+    let id = Id::new();
+    instructions.push(Instruction::Make {
+        id: id.clone(),
+        instance: table_instance.clone(),
+        class: test_class,
+        args,
+    });
+    expected_result.push((ExpectedResult::ok(id, position.clone()), snoozed.clone()));
+    let id = Id::new();
+    instructions.push(Instruction::Call {
+        id: id.clone(),
+        instance: table_instance,
+        function: "query".into(),
+        args: Vec::new(),
+    });
+    expected_result.push((
+        expected_table(
+            id,
+            position.clone(),
+            MethodName("query".into(), position),
+            table.into_iter().map(ExpectedRow::from).collect(),
+        ),
+        snoozed,
+    ));
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ExpectedRow {
+    pub position: Position,
+    pub cells: Vec<(String, ExpectedResultValue)>,
+}
+
+impl From<QueryRow> for ExpectedRow {
+    fn from(row: QueryRow) -> Self {
+        Self {
+            position: row.position,
+            cells: row
+                .cells
+                .into_iter()
+                .map(|(column, value)| (column.0, ExpectedResultValue::cell(value.0)))
+                .collect(),
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+impl Display for ExpectedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}]",
+            self.cells
+                .iter()
+                .map(|(column, value)| format!("{column}={value}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub enum ExpectedResultValue {
     #[cfg(test)]
     Any,
@@ -287,9 +718,187 @@ pub enum ExpectedResultValue {
     NullOrVoid,
     NullOrVoidOrMethodNotFound,
     String(String),
+    NotString(String),
+    NoException,
+    /// A blank cell, which shows the actual value instead of checking it.
+    Blank,
+    /// Any exception.
+    Error,
+    /// An exception whose message contains the text.
+    ErrorContaining(String),
+    /// An exception of the class, with or without its package.
+    Throws(String),
+    /// A number compared with the operand, like `>= 10`.
+    Comparison(Comparison, String),
+    /// A number between the bounds, like `3 < _ <= 7`.
+    Range {
+        low: String,
+        low_comparison: Comparison,
+        high_comparison: Comparison,
+        high: String,
+    },
+    /// A number equal to the operand when rounded to its decimals, like `~= 3.14`.
+    Approximately(String),
+    /// A string matching the regular expression, like `=~/^order-\d+$/`.
+    Matches(Pattern),
+    /// A `=~/pattern/` whose pattern is not a valid regular expression, failing the cell.
+    InvalidPattern {
+        pattern: String,
+        error: String,
+    },
     SetSymbol(String),
     Symbol(String),
     List(Vec<ExpectedResultValue>),
+    Table(Vec<ExpectedRow>),
+    OrderedTable(Vec<ExpectedRow>),
+    SubsetTable(Vec<ExpectedRow>),
+}
+
+impl ExpectedResultValue {
+    /// Parses the expressions a cell can have instead of the exact value. Cells expecting the call
+    /// to fail are `error`, `error: <part of the message>` and `throws <exception class>`, numbers
+    /// can be compared with `< 5`, `>= 10`, `3 < _ <= 7`, `!= 0` or `~= 3.14`, and strings matched
+    /// with `=~/pattern/`. Markdown drops the backslash escaping a punctuation character, so those
+    /// patterns are written in inline code, like `` `=~/^3\.14$/` ``. A blank cell accepts any value
+    /// but an exception, while `(blank)` expects an empty string. A cell between double quotes is
+    /// the exact string inside them, so `"error"` expects the string `error` and `""` an empty one.
+    /// A `$$` is a literal `$`, so `$$name` expects `$name` instead of the value of the symbol.
+    pub fn cell(value: String) -> Self {
+        let value = unescape(&value);
+        match value.as_str() {
+            "" => return ExpectedResultValue::Blank,
+            "(blank)" => return ExpectedResultValue::String(String::new()),
+            _ => {}
+        }
+        if let Some(literal) = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+        {
+            return ExpectedResultValue::String(literal.to_string());
+        }
+        if value == "error" {
+            return ExpectedResultValue::Error;
+        }
+        if let Some(message) = value.strip_prefix("error:") {
+            return ExpectedResultValue::ErrorContaining(message.trim().to_string());
+        }
+        if let Some(class_name) = value.strip_prefix("throws ") {
+            return ExpectedResultValue::Throws(class_name.trim().to_string());
+        }
+        if let Some(pattern) = value
+            .strip_prefix("=~/")
+            .and_then(|pattern| pattern.strip_suffix('/'))
+        {
+            return match Regex::new(pattern) {
+                Ok(regex) => ExpectedResultValue::Matches(Pattern(regex)),
+                Err(err) => ExpectedResultValue::InvalidPattern {
+                    pattern: pattern.into(),
+                    error: err.to_string(),
+                },
+            };
+        }
+        numeric_expression(&value).unwrap_or(ExpectedResultValue::String(value))
+    }
+}
+
+/// A regular expression of a cell, compared with others by its pattern.
+#[derive(Debug, Clone)]
+pub struct Pattern(pub Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+fn numeric_expression(value: &str) -> Option<ExpectedResultValue> {
+    if let Some(operand) = value.strip_prefix("~=") {
+        let operand = operand.trim();
+        return is_number(operand).then(|| ExpectedResultValue::Approximately(operand.into()));
+    }
+    if let Some(range) = range_expression(value) {
+        return Some(range);
+    }
+    for (prefix, comparison) in [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ] {
+        if let Some(operand) = value.strip_prefix(prefix) {
+            let operand = operand.trim();
+            return match comparison {
+                _ if is_number(operand) => {
+                    Some(ExpectedResultValue::Comparison(comparison, operand.into()))
+                }
+                Comparison::NotEqual => Some(ExpectedResultValue::NotString(operand.into())),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+fn range_expression(value: &str) -> Option<ExpectedResultValue> {
+    let (low, high) = value.split_once('_')?;
+    let low = low.trim_end();
+    let (low, low_comparison) = match low.strip_suffix("<=") {
+        Some(low) => (low, Comparison::LessOrEqual),
+        None => (low.strip_suffix('<')?, Comparison::Less),
+    };
+    let high = high.trim_start();
+    let (high_comparison, high) = match high.strip_prefix("<=") {
+        Some(high) => (Comparison::LessOrEqual, high),
+        None => (Comparison::Less, high.strip_prefix('<')?),
+    };
+    let (low, high) = (low.trim(), high.trim());
+    (is_number(low) && is_number(high)).then(|| ExpectedResultValue::Range {
+        low: low.into(),
+        low_comparison,
+        high_comparison,
+        high: high.into(),
+    })
+}
+
+fn is_number(value: &str) -> bool {
+    value.parse::<f64>().is_ok()
+}
+
+/// How a number is compared with the operand of an expression.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn compare(&self, number: f64, operand: f64) -> bool {
+        match self {
+            Comparison::Less => number < operand,
+            Comparison::LessOrEqual => number <= operand,
+            Comparison::Greater => number > operand,
+            Comparison::GreaterOrEqual => number >= operand,
+            Comparison::NotEqual => number != operand,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Less => write!(f, "<"),
+            Comparison::LessOrEqual => write!(f, "<="),
+            Comparison::Greater => write!(f, ">"),
+            Comparison::GreaterOrEqual => write!(f, ">="),
+            Comparison::NotEqual => write!(f, "!="),
+        }
+    }
 }
 
 impl Display for ExpectedResultValue {
@@ -303,6 +912,28 @@ impl Display for ExpectedResultValue {
             }
             ExpectedResultValue::Ok => write!(f, "OK"),
             ExpectedResultValue::String(value) => write!(f, "`{}`", value),
+            ExpectedResultValue::NotString(value) => write!(f, "NOT `{}`", value),
+            ExpectedResultValue::NoException => write!(f, "NO EXCEPTION"),
+            ExpectedResultValue::Blank => write!(f, "ANY VALUE"),
+            ExpectedResultValue::Error => write!(f, "ERROR"),
+            ExpectedResultValue::ErrorContaining(message) => write!(f, "ERROR `{}`", message),
+            ExpectedResultValue::Throws(class_name) => write!(f, "THROWS `{}`", class_name),
+            ExpectedResultValue::Comparison(comparison, operand) => {
+                write!(f, "`{} {}`", comparison, operand)
+            }
+            ExpectedResultValue::Range {
+                low,
+                low_comparison,
+                high_comparison,
+                high,
+            } => write!(
+                f,
+                "`{} {} _ {} {}`",
+                low, low_comparison, high_comparison, high
+            ),
+            ExpectedResultValue::Approximately(operand) => write!(f, "`~= {}`", operand),
+            ExpectedResultValue::Matches(pattern) => write!(f, "=~/{}/", pattern.0.as_str()),
+            ExpectedResultValue::InvalidPattern { pattern, .. } => write!(f, "=~/{}/", pattern),
             ExpectedResultValue::SetSymbol(value) => write!(f, "SET SYMBOL `{}`", value),
             ExpectedResultValue::Symbol(value) => write!(f, "SYMBOL `{}`", value),
             ExpectedResultValue::List(value) => {
@@ -316,22 +947,41 @@ impl Display for ExpectedResultValue {
                         .join(",")
                 )
             }
+            ExpectedResultValue::Table(rows) => write!(f, "{}", rows_to_string(rows)),
+            ExpectedResultValue::OrderedTable(rows) => {
+                write!(f, "ORDERED {}", rows_to_string(rows))
+            }
+            ExpectedResultValue::SubsetTable(rows) => {
+                write!(f, "SUBSET {}", rows_to_string(rows))
+            }
         }
     }
 }
 
+fn rows_to_string(rows: &[ExpectedRow]) -> String {
+    format!(
+        "[{}]",
+        rows.iter()
+            .map(|row| row.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::bail;
     use chrono::NaiveDate;
 
-    use crate::processor::markdown_commands::{DecisionTableType, Snooze, TableRow};
+    use crate::processor::markdown_commands::{
+        Column, DecisionTableType, ScriptAction, ScriptRow, Snooze, TableRow,
+    };
 
     use super::*;
 
     #[test]
     fn import() -> Result<()> {
-        let (instructions, expected_result) =
+        let (instructions, expected_result, _) =
             get_instructions_from_commands(vec![MarkdownCommand::Import {
                 path: "Fixtures".into(),
                 position: Position::new(0, 0),
@@ -349,11 +999,78 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn cell_expressions() {
+        let cell = |value: &str| ExpectedResultValue::cell(value.into());
+        assert_eq!(ExpectedResultValue::Error, cell("error"));
+        assert_eq!(
+            ExpectedResultValue::ErrorContaining("Invalid amount".into()),
+            cell("error: Invalid amount")
+        );
+        assert_eq!(
+            ExpectedResultValue::Throws("IllegalArgumentException".into()),
+            cell("throws IllegalArgumentException")
+        );
+        assert_eq!(
+            ExpectedResultValue::Comparison(Comparison::Less, "5".into()),
+            cell("< 5")
+        );
+        assert_eq!(
+            ExpectedResultValue::Comparison(Comparison::GreaterOrEqual, "10".into()),
+            cell(">=10")
+        );
+        assert_eq!(
+            ExpectedResultValue::Comparison(Comparison::NotEqual, "0".into()),
+            cell("!= 0")
+        );
+        assert_eq!(
+            ExpectedResultValue::NotString("error".into()),
+            cell("!= error")
+        );
+        assert_eq!(
+            ExpectedResultValue::Range {
+                low: "3".into(),
+                low_comparison: Comparison::Less,
+                high_comparison: Comparison::LessOrEqual,
+                high: "7".into(),
+            },
+            cell("3 < _ <= 7")
+        );
+        assert_eq!(
+            ExpectedResultValue::Approximately("3.14".into()),
+            cell("~= 3.14")
+        );
+        assert_eq!(ExpectedResultValue::String("< a".into()), cell("< a"));
+        assert_eq!(
+            ExpectedResultValue::String("snake_case".into()),
+            cell("snake_case")
+        );
+        assert_eq!(
+            ExpectedResultValue::String("a < _ < 7".into()),
+            cell("a < _ < 7")
+        );
+        assert_eq!(
+            ExpectedResultValue::Matches(Pattern(Regex::new("^order-\\d+$").unwrap())),
+            cell("=~/^order-\\d+$/")
+        );
+        assert_eq!(ExpectedResultValue::Blank, cell(""));
+        assert_eq!(ExpectedResultValue::String("".into()), cell("\"\""));
+        assert_eq!(ExpectedResultValue::String("".into()), cell("(blank)"));
+        assert_eq!(
+            ExpectedResultValue::InvalidPattern {
+                pattern: "(".into(),
+                error: "regex parse error:\n    (\n    ^\nerror: unclosed group".into(),
+            },
+            cell("=~/(/")
+        );
+    }
+
     #[test]
     fn decision_table() -> Result<()> {
         let position = Position::new(0, 0);
-        let (mut instructions, mut expected_result) =
+        let (mut instructions, mut expected_result, _) =
             get_instructions_from_commands(vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Class".into(), position.clone()),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![
@@ -472,7 +1189,7 @@ mod test {
                 instance: expected_instance,
                 function,
                 args
-            } if *expected_instance == instance && function == "setA" && args == &["1".to_string()]
+            } if *expected_instance == instance && function == "setA" && args == ["1".to_string()]
         ));
         assert!(matches!(
             expected_result.remove(0),
@@ -681,8 +1398,9 @@ mod test {
     #[test]
     fn snooze() -> Result<()> {
         let position = Position::new(0, 0);
-        let (mut instructions, mut expected_result) =
+        let (mut instructions, mut expected_result, _) =
             get_instructions_from_commands(vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Class".into(), position.clone()),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![TableRow {
@@ -747,7 +1465,7 @@ mod test {
                 instance: expected_instance,
                 function,
                 args
-            } if *expected_instance == instance && function == "setA" && args == &["1".to_string()]
+            } if *expected_instance == instance && function == "setA" && args == ["1".to_string()]
         ));
         assert!(matches!(
             expected_result.remove(0),
@@ -803,8 +1521,9 @@ mod test {
     #[test]
     fn decision_table_single_method() -> Result<()> {
         let position = Position::new(0, 0);
-        let (mut instructions, mut expected_result) =
+        let (mut instructions, mut expected_result, _) =
             get_instructions_from_commands(vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Class".into(), position.clone()),
                 r#type: DecisionTableType::SingleMethod(MethodName(
                     "Method".into(),
@@ -926,7 +1645,7 @@ mod test {
                 instance: expected_instance,
                 function,
                 args
-            } if *expected_instance == instance && function == "Method" && args == &["1".to_string(), "2".to_string()]
+            } if *expected_instance == instance && function == "Method" && args == ["1".to_string(), "2".to_string()]
         ));
         assert!(matches!(
             expected_result.remove(0),
@@ -963,7 +1682,7 @@ mod test {
                 instance: expected_instance,
                 function,
                 args
-            } if *expected_instance == instance && function == "Method" && args == &["3".to_string(), "4".to_string()]
+            } if *expected_instance == instance && function == "Method" && args == ["3".to_string(), "4".to_string()]
         ));
         assert!(matches!(
             expected_result.remove(0),
@@ -1000,8 +1719,9 @@ mod test {
     #[test]
     fn test_symbols() -> Result<()> {
         let position = Position::new(0, 0);
-        let (mut instructions, mut expected_result) =
+        let (mut instructions, mut expected_result, _) =
             get_instructions_from_commands(vec![MarkdownCommand::DecisionTable {
+                args: vec![],
                 class: Class("Class".into(), position.clone()),
                 r#type: DecisionTableType::MultipleSetterAndGetters,
                 table: vec![
@@ -1195,4 +1915,480 @@ mod test {
         ));
         Ok(())
     }
+
+    #[test]
+    fn script_table() -> Result<()> {
+        let position = Position::new(0, 0);
+        let row = |method: &str, args: Vec<&str>, action: ScriptAction| ScriptRow {
+            position: position.clone(),
+            method: MethodName(method.into(), position.clone()),
+            args: args
+                .into_iter()
+                .map(|arg| Value(arg.into(), position.clone()))
+                .collect(),
+            action,
+        };
+        let (mut instructions, mut expected_result, _) =
+            get_instructions_from_commands(vec![MarkdownCommand::ScriptTable {
+                args: vec!["tenant".into(), "$CONFIG".into()],
+                class: Class("Class".into(), position.clone()),
+                table: vec![
+                    row("login", vec!["bob"], ScriptAction::Call),
+                    row(
+                        "user",
+                        vec![],
+                        ScriptAction::Check(Value("bob".into(), position.clone())),
+                    ),
+                    row(
+                        "user",
+                        vec![],
+                        ScriptAction::Check(Value("$V".into(), position.clone())),
+                    ),
+                    row(
+                        "user",
+                        vec![],
+                        ScriptAction::CheckNot(Value("alice".into(), position.clone())),
+                    ),
+                    row("logged", vec![], ScriptAction::Ensure),
+                    row("admin", vec![], ScriptAction::Reject),
+                    row("name", vec![], ScriptAction::Show),
+                    row("user", vec![], ScriptAction::SetSymbol("V".into())),
+                ],
+                snoozed: Snooze::not_snooze(),
+            }])?;
+        assert_eq!(9, instructions.len());
+        assert_eq!(instructions.len(), expected_result.len());
+        let Instruction::Make {
+            id,
+            instance,
+            class,
+            args,
+        } = instructions.remove(0)
+        else {
+            bail!("Expected make");
+        };
+        assert_eq!("Class", class);
+        assert_eq!(vec!["tenant".to_string(), "$CONFIG".to_string()], args);
+        assert!(
+            matches!(expected_result.remove(0), (ExpectedResult { id: expected_id, position:_, method_name: None, value: ExpectedResultValue::Ok }, _) if expected_id == id)
+        );
+        assert!(matches!(
+            instructions.remove(0),
+            Instruction::Call {
+                id: _,
+                instance: expected_instance,
+                function,
+                args
+            } if *expected_instance == instance && function == "login" && args == ["bob".to_string()]
+        ));
+        assert!(matches!(
+            expected_result.remove(0),
+            (ExpectedResult { id: _, position:_, method_name: Some(method), value: ExpectedResultValue::NotString(value) }, _) if method.0 == "login" && value == "false"
+        ));
+        for expected_value in [
+            ExpectedResultValue::String("bob".into()),
+            ExpectedResultValue::Symbol("V".into()),
+            ExpectedResultValue::NotString("alice".into()),
+            ExpectedResultValue::String("true".into()),
+            ExpectedResultValue::String("false".into()),
+            ExpectedResultValue::NoException,
+        ] {
+            assert!(matches!(
+                instructions.remove(0),
+                Instruction::Call {
+                    id: _,
+                    instance: expected_instance,
+                    function: _,
+                    args
+                } if *expected_instance == instance && args.is_empty()
+            ));
+            assert_eq!(expected_value, expected_result.remove(0).0.value);
+        }
+        assert!(matches!(
+            instructions.remove(0),
+            Instruction::CallAndAssign {
+                id: _,
+                instance: expected_instance,
+                function,
+                symbol,
+                args
+            } if *expected_instance == instance && function == "user" && symbol == "V" && args.is_empty()
+        ));
+        assert!(matches!(
+            expected_result.remove(0),
+            (ExpectedResult { id: _, position:_, method_name: Some(method), value: ExpectedResultValue::SetSymbol(symbol) }, _) if symbol == "V" && method.0 == "user"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn query_table() -> Result<()> {
+        let position = Position::new(0, 0);
+        let (mut instructions, mut expected_result, _) =
+            get_instructions_from_commands(vec![MarkdownCommand::QueryTable {
+                args: vec![],
+                class: Class("Class".into(), position.clone()),
+                table: vec![QueryRow {
+                    position: Position::new(1, 0),
+                    cells: vec![(
+                        Column("name".into(), position.clone()),
+                        Value("bob".into(), position.clone()),
+                    )],
+                }],
+                snoozed: Snooze::not_snooze(),
+            }])?;
+        assert_eq!(2, instructions.len());
+        assert_eq!(instructions.len(), expected_result.len());
+        let Instruction::Make {
+            id,
+            instance,
+            class,
+            args,
+        } = instructions.remove(0)
+        else {
+            bail!("Expected make");
+        };
+        assert_eq!("Class", class);
+        assert!(args.is_empty());
+        assert!(
+            matches!(expected_result.remove(0), (ExpectedResult { id: expected_id, position:_, method_name: None, value: ExpectedResultValue::Ok }, _) if expected_id == id)
+        );
+        assert!(matches!(
+            instructions.remove(0),
+            Instruction::Call {
+                id: _,
+                instance: expected_instance,
+                function,
+                args
+            } if *expected_instance == instance && function == "query" && args.is_empty()
+        ));
+        assert_eq!(
+            ExpectedResultValue::Table(vec![ExpectedRow {
+                position: Position::new(1, 0),
+                cells: vec![("name".into(), ExpectedResultValue::String("bob".into()))],
+            }]),
+            expected_result.remove(0).0.value
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_and_subset_query_tables() -> Result<()> {
+        let position = Position::new(0, 0);
+        let table = vec![QueryRow {
+            position: Position::new(1, 0),
+            cells: vec![(
+                Column("name".into(), position.clone()),
+                Value("bob".into(), position.clone()),
+            )],
+        }];
+        let expected_rows = || {
+            vec![ExpectedRow {
+                position: Position::new(1, 0),
+                cells: vec![("name".into(), ExpectedResultValue::String("bob".into()))],
+            }]
+        };
+        let (instructions, mut expected_result, _) = get_instructions_from_commands(vec![
+            MarkdownCommand::OrderedQueryTable {
+                args: vec![],
+                class: Class("Class".into(), position.clone()),
+                table: table.clone(),
+                snoozed: Snooze::not_snooze(),
+            },
+            MarkdownCommand::SubsetQueryTable {
+                args: vec![],
+                class: Class("Class".into(), position.clone()),
+                table,
+                snoozed: Snooze::not_snooze(),
+            },
+        ])?;
+        assert_eq!(4, instructions.len());
+        assert_eq!(instructions.len(), expected_result.len());
+        assert!(matches!(
+            &instructions[3],
+            Instruction::Call {
+                id: _,
+                instance: _,
+                function,
+                args
+            } if function == "query" && args.is_empty()
+        ));
+        assert_eq!(
+            ExpectedResultValue::SubsetTable(expected_rows()),
+            expected_result.remove(3).0.value
+        );
+        assert_eq!(
+            ExpectedResultValue::OrderedTable(expected_rows()),
+            expected_result.remove(1).0.value
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scenario() -> Result<()> {
+        let row = |line: usize, method: &str, args: Vec<&str>, action: ScriptAction| ScriptRow {
+            position: Position::new(line, 1),
+            method: MethodName(method.into(), Position::new(line, 3)),
+            args: args
+                .into_iter()
+                .map(|arg| Value(arg.into(), Position::new(line, 10)))
+                .collect(),
+            action,
+        };
+        let (instructions, expected_result, _) = get_instructions_from_commands(vec![
+            MarkdownCommand::Scenario {
+                name: MethodName("login".into(), Position::new(1, 1)),
+                parameters: vec!["user".into(), "username".into()],
+                table: vec![
+                    row(
+                        3,
+                        "enterUser",
+                        vec!["@user/@{username}"],
+                        ScriptAction::Call,
+                    ),
+                    row(
+                        4,
+                        "user",
+                        vec![],
+                        ScriptAction::Check(Value("@username".into(), Position::new(4, 10))),
+                    ),
+                ],
+            },
+            MarkdownCommand::ScriptTable {
+                args: vec![],
+                class: Class("Class".into(), Position::new(6, 1)),
+                table: vec![row(8, "login", vec!["bob", "bobby"], ScriptAction::Call)],
+                snoozed: Snooze::not_snooze(),
+            },
+            MarkdownCommand::ScenarioTable {
+                class: Class("login".into(), Position::new(10, 1)),
+                actor: Class("Class".into(), Position::new(10, 1)),
+                args: vec![],
+                table: vec![row(12, "login", vec!["alice", "al"], ScriptAction::Call)],
+                snoozed: Snooze::not_snooze(),
+            },
+        ])?;
+        assert_eq!(6, instructions.len());
+        assert_eq!(instructions.len(), expected_result.len());
+        let calls_on = |instructions: &[Instruction]| {
+            let [Instruction::Make { instance, .. }, calls @ ..] = instructions else {
+                bail!("Expected make");
+            };
+            calls
+                .iter()
+                .map(|instruction| match instruction {
+                    Instruction::Call {
+                        instance: call_instance,
+                        function,
+                        args,
+                        ..
+                    } if call_instance == instance => Ok((function.clone(), args.clone())),
+                    other => bail!("Expected a call on the table instance, got {other:?}"),
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        assert_eq!(
+            vec![
+                ("enterUser".to_string(), vec!["bob/bobby".to_string()]),
+                ("user".to_string(), vec![]),
+            ],
+            calls_on(&instructions[..3])?
+        );
+        assert_eq!(
+            vec![
+                ("enterUser".to_string(), vec!["alice/al".to_string()]),
+                ("user".to_string(), vec![]),
+            ],
+            calls_on(&instructions[3..])?
+        );
+        assert_eq!(
+            ExpectedResultValue::String("bobby".into()),
+            expected_result[2].0.value
+        );
+        assert_eq!(
+            "4:10 via scenario call at 8:1",
+            expected_result[2].0.position.to_string()
+        );
+        assert_eq!(
+            "3:1 via scenario call at 12:1",
+            expected_result[4].0.position.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn scenario_table_makes_its_own_actor_without_a_script_table() -> Result<()> {
+        let (instructions, expected_result, _) = get_instructions_from_commands(vec![
+            MarkdownCommand::Scenario {
+                name: MethodName("login".into(), Position::new(1, 1)),
+                parameters: vec!["user".into()],
+                table: vec![ScriptRow {
+                    position: Position::new(3, 1),
+                    method: MethodName("enterUser".into(), Position::new(3, 3)),
+                    args: vec![Value("@user".into(), Position::new(3, 10))],
+                    action: ScriptAction::Call,
+                }],
+            },
+            MarkdownCommand::ScenarioTable {
+                class: Class("login".into(), Position::new(5, 1)),
+                actor: Class("Actor".into(), Position::new(5, 1)),
+                args: vec!["arg".into()],
+                table: vec![ScriptRow {
+                    position: Position::new(9, 1),
+                    method: MethodName("login".into(), Position::new(5, 1)),
+                    args: vec![Value("bob".into(), Position::new(9, 3))],
+                    action: ScriptAction::Call,
+                }],
+                snoozed: Snooze::not_snooze(),
+            },
+        ])?;
+        let [Instruction::Make {
+            instance,
+            class,
+            args,
+            ..
+        }, Instruction::Call {
+            instance: call_instance,
+            function,
+            args: call_args,
+            ..
+        }] = &instructions[..]
+        else {
+            bail!("Expected a make and a call, got {instructions:?}");
+        };
+        assert_eq!("Actor", class);
+        assert_eq!(&vec!["arg".to_string()], args);
+        assert_eq!(instance, call_instance);
+        assert_eq!("enterUser", function);
+        assert_eq!(&vec!["bob".to_string()], call_args);
+        assert_eq!(ExpectedResultValue::Ok, expected_result[0].0.value);
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_symbols_are_literal() -> Result<()> {
+        let position = Position::new(1, 1);
+        let (_, expected_result, _) = get_instructions_from_commands(vec![
+            MarkdownCommand::DecisionTable {
+                args: vec![],
+                class: Class("Class".into(), position.clone()),
+                r#type: DecisionTableType::MultipleSetterAndGetters,
+                table: vec![TableRow {
+                    position: position.clone(),
+                    setters: vec![],
+                    getters: vec![
+                        (
+                            MethodName("getA".into(), position.clone()),
+                            Value("$$V=".into(), position.clone()),
+                        ),
+                        (
+                            MethodName("getB".into(), position.clone()),
+                            Value("$$V".into(), position.clone()),
+                        ),
+                    ],
+                }],
+                snoozed: Snooze::not_snooze(),
+            },
+            MarkdownCommand::ScriptTable {
+                args: vec![],
+                class: Class("Class".into(), position.clone()),
+                table: vec![ScriptRow {
+                    position: position.clone(),
+                    method: MethodName("price".into(), position.clone()),
+                    args: vec![],
+                    action: ScriptAction::Check(Value("$$5 or $$V".into(), position.clone())),
+                }],
+                snoozed: Snooze::not_snooze(),
+            },
+        ])?;
+        let values: Vec<_> = expected_result
+            .into_iter()
+            .map(|(expected, _)| expected.value)
+            .filter(|value| matches!(value, ExpectedResultValue::String(_)))
+            .collect();
+        assert_eq!(
+            vec![
+                ExpectedResultValue::String("$V=".into()),
+                ExpectedResultValue::String("$V".into()),
+                ExpectedResultValue::String("$5 or $V".into()),
+            ],
+            values
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn library() -> Result<()> {
+        let (instructions, expected_result, _) =
+            get_instructions_from_commands(vec![MarkdownCommand::Library {
+                class: Class("Library".into(), Position::new(1, 1)),
+                args: vec!["arg".into()],
+            }])?;
+        assert!(matches!(
+            &instructions[..],
+            [Instruction::Make {
+                id: _,
+                instance,
+                class,
+                args
+            }] if instance.starts_with("library") && class == "Library" && args == &["arg".to_string()]
+        ));
+        assert!(matches!(
+            &expected_result[..],
+            [(
+                ExpectedResult {
+                    id: _,
+                    position: _,
+                    method_name: None,
+                    value: ExpectedResultValue::Ok
+                },
+                _
+            )]
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cases() -> Result<()> {
+        let (_, expected_result, test_cases) = get_instructions_from_commands(vec![
+            MarkdownCommand::Import {
+                path: "Fixtures".into(),
+                position: Position::new(1, 1),
+            },
+            MarkdownCommand::Scenario {
+                name: MethodName("login".into(), Position::new(3, 1)),
+                parameters: vec![],
+                table: vec![],
+            },
+            MarkdownCommand::QueryTable {
+                args: vec![],
+                class: Class("Users".into(), Position::new(7, 1)),
+                table: vec![],
+                snoozed: Snooze::not_snooze(),
+            },
+        ])?;
+        assert_eq!(
+            vec![
+                TestCase {
+                    name: "import Fixtures".into(),
+                    position: Position::new(1, 1),
+                    expected_results: 1,
+                },
+                TestCase {
+                    name: "Users".into(),
+                    position: Position::new(7, 1),
+                    expected_results: 2,
+                },
+            ],
+            test_cases
+        );
+        assert_eq!(
+            expected_result.len(),
+            test_cases
+                .iter()
+                .map(|test_case| test_case.expected_results)
+                .sum::<usize>()
+        );
+        Ok(())
+    }
 }
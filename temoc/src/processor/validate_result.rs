@@ -1,64 +1,130 @@
 use std::fmt::Display;
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{bail, Result};
 
 use slim_protocol::{InstructionResult, InstructionResultValue};
 
 use super::{
-    markdown_commands::Snooze,
-    slim_instructions_from_commands::{ExpectedResult, ExpectedResultValue},
+    markdown_commands::{Position, Snooze},
+    slim_instructions_from_commands::{ExpectedResult, ExpectedResultValue, ExpectedRow},
     State,
 };
 
+/// The result of an instruction checked against the result expected by the markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedResult {
+    pub expected: ExpectedResult,
+    pub actual: InstructionResultValue,
+    pub snooze: Snooze,
+    pub failures: Vec<Failure>,
+}
+
+/// A failure message and the position in the markdown it refers to, like a cell or a row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub position: Position,
+    pub message: String,
+}
+
+impl CheckedResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
 pub fn validate_result(
     file_path: impl Display,
     expected_result: Vec<(ExpectedResult, Snooze)>,
     result: Vec<InstructionResult>,
     state: &mut State,
-) -> Result<Vec<(String, Snooze)>> {
-    let mut failures = Vec::new();
+) -> Result<Vec<CheckedResult>> {
+    let file_path = file_path.to_string();
+    let mut checked_results = Vec::new();
     if expected_result.len() != result.len() {
         bail!("Number of instruction results `{}` does not matched the expected number of results `{}`", result.len(), expected_result.len())
     }
-    for (result, (mut expected_result, snooze)) in
-        result.into_iter().zip(expected_result.into_iter())
-    {
+    for (result, (mut expected_result, snooze)) in result.into_iter().zip(expected_result) {
+        let mut failures = Vec::new();
         if expected_result.id != result.id {
-            failures.push((
-                format!(
+            failures.push(Failure {
+                position: expected_result.position.clone(),
+                message: format!(
                     "Different ID in response. Expected {} but got {}",
                     expected_result.id, result.id
                 ),
-                snooze.clone(),
-            ));
+            });
+            checked_results.push(CheckedResult {
+                expected: expected_result,
+                actual: result.value,
+                snooze,
+                failures,
+            });
             continue;
         }
-        if let ExpectedResultValue::Symbol(symbol) = expected_result.value {
-            expected_result.value = ExpectedResultValue::String(
-                state
-                    .get_symbol(&symbol)
-                    .ok_or_else(|| anyhow!("Symbol `{}` not found", symbol))?
-                    .clone(),
-            );
+        if let ExpectedResultValue::Symbol(symbol) = &expected_result.value {
+            let Some(value) = state.get_symbol(symbol) else {
+                failures.push(Failure {
+                    position: expected_result.position.clone(),
+                    message: format!(
+                        "Undefined symbol `{symbol}` in {}",
+                        expected_result.position.in_file(&file_path)
+                    ),
+                });
+                checked_results.push(CheckedResult {
+                    expected: expected_result,
+                    actual: result.value,
+                    snooze,
+                    failures,
+                });
+                continue;
+            };
+            expected_result.value = ExpectedResultValue::String(value.clone());
         }
-        if expected_result.value != result.value {
-            failures.push((
-                format!(
+        let row_diff = query_rows(&result.value).and_then(|actual_rows| {
+            row_diff(&expected_result.value, &actual_rows)
+                .map(|diff| row_diff_failures(&file_path, &expected_result, diff))
+        });
+        let invalid_patterns = invalid_patterns(&expected_result.value);
+        if let Some(row_diff_failures) = row_diff {
+            failures.extend(row_diff_failures);
+        } else if !invalid_patterns.is_empty() {
+            let position = expected_result.position.in_file(&file_path);
+            failures.extend(
+                invalid_patterns
+                    .into_iter()
+                    .map(|(pattern, error)| Failure {
+                        position: expected_result.position.clone(),
+                        message: format!(
+                            "Invalid regular expression `{pattern}` in {position}: {error}"
+                        ),
+                    }),
+            );
+        } else if expected_result.value != result.value {
+            failures.push(Failure {
+                position: expected_result.position.clone(),
+                message: format!(
                     "Expected {}, got {} {}",
                     expected_result.value,
                     result.value,
                     failure_expected_result_detail_message(&file_path, &expected_result)
                 ),
-                snooze.clone(),
-            ));
+            });
         }
-        if let (ExpectedResultValue::SetSymbol(symbol), InstructionResultValue::String(value)) =
-            (expected_result.value, result.value)
-        {
-            state.set_symbol(symbol, value);
+        match (&expected_result.value, &result.value) {
+            (ExpectedResultValue::SetSymbol(symbol), InstructionResultValue::String(value)) => {
+                state.set_symbol(symbol.clone(), value.clone())
+            }
+            (ExpectedResultValue::SetSymbol(symbol), _) => state.remove_symbol(symbol),
+            _ => {}
         }
+        checked_results.push(CheckedResult {
+            expected: expected_result,
+            actual: result.value,
+            snooze,
+            failures,
+        });
     }
-    Ok(failures)
+    Ok(checked_results)
 }
 
 impl PartialEq<InstructionResultValue> for ExpectedResultValue {
@@ -110,37 +176,376 @@ impl PartialEq<InstructionResultValue> for ExpectedResultValue {
                 }
                 true
             }
+            (ExpectedResultValue::NotString(_), InstructionResultValue::Exception(_)) => false,
+            (
+                ExpectedResultValue::NotString(expected_value),
+                InstructionResultValue::String(actual_value),
+            ) => expected_value != actual_value,
+            (ExpectedResultValue::NotString(_), _) => true,
+            (ExpectedResultValue::NoException, InstructionResultValue::Exception(_)) => false,
+            (ExpectedResultValue::NoException, _) => true,
+            (ExpectedResultValue::Blank, InstructionResultValue::Exception(_)) => false,
+            (ExpectedResultValue::Blank, _) => true,
+            (ExpectedResultValue::Error, InstructionResultValue::Exception(_)) => true,
+            (
+                ExpectedResultValue::ErrorContaining(expected_message),
+                InstructionResultValue::Exception(message),
+            ) => message
+                .pretty_message()
+                .unwrap_or(message.raw_message())
+                .contains(expected_message.as_str()),
+            (
+                ExpectedResultValue::Throws(expected_class),
+                InstructionResultValue::Exception(message),
+            ) => message.class_name().is_some_and(|class_name| {
+                class_name == expected_class || class_name.ends_with(&format!(".{expected_class}"))
+            }),
+            (
+                ExpectedResultValue::Comparison(comparison, operand),
+                InstructionResultValue::String(actual_value),
+            ) => numbers(actual_value, operand)
+                .is_some_and(|(actual, operand)| comparison.compare(actual, operand)),
+            (
+                ExpectedResultValue::Range {
+                    low,
+                    low_comparison,
+                    high_comparison,
+                    high,
+                },
+                InstructionResultValue::String(actual_value),
+            ) => {
+                numbers(actual_value, low)
+                    .is_some_and(|(actual, low)| low_comparison.compare(low, actual))
+                    && numbers(actual_value, high)
+                        .is_some_and(|(actual, high)| high_comparison.compare(actual, high))
+            }
+            (
+                ExpectedResultValue::Approximately(operand),
+                InstructionResultValue::String(actual_value),
+            ) => {
+                // Rounded to the decimals of the operand, so `~= 3.14` matches `3.1415`.
+                let precision = 10f64.powi(decimals(operand));
+                numbers(actual_value, operand).is_some_and(|(actual, operand)| {
+                    actual == operand
+                        || (actual * precision).round() == (operand * precision).round()
+                })
+            }
+            (
+                ExpectedResultValue::Matches(pattern),
+                InstructionResultValue::String(actual_value),
+            ) => pattern.0.is_match(actual_value),
             (ExpectedResultValue::SetSymbol(_), InstructionResultValue::String(_)) => true,
+            (
+                ExpectedResultValue::Table(_)
+                | ExpectedResultValue::OrderedTable(_)
+                | ExpectedResultValue::SubsetTable(_),
+                other,
+            ) => query_rows(other).is_some_and(|actual_rows| {
+                row_diff(self, &actual_rows).is_some_and(|diff| diff.is_empty())
+            }),
             _ => false,
         }
     }
 }
 
+/// The patterns that can't be compiled of a cell, or of the cells of a single method row, with
+/// their errors.
+fn invalid_patterns(expected: &ExpectedResultValue) -> Vec<(&str, &str)> {
+    match expected {
+        ExpectedResultValue::InvalidPattern { pattern, error } => vec![(pattern, error)],
+        ExpectedResultValue::List(values) => values.iter().flat_map(invalid_patterns).collect(),
+        _ => vec![],
+    }
+}
+
+/// The actual value and the operand of a numeric expression, if both are numbers.
+fn numbers(actual_value: &str, operand: &str) -> Option<(f64, f64)> {
+    Some((actual_value.trim().parse().ok()?, operand.parse().ok()?))
+}
+
+fn decimals(number: &str) -> i32 {
+    number
+        .split_once('.')
+        .map(|(_, decimals)| decimals.len() as i32)
+        .unwrap_or(0)
+}
+
 fn failure_expected_result_detail_message(
-    file_path: impl Display,
+    file_path: &str,
     expected_result: &ExpectedResult,
 ) -> String {
-    let position = &expected_result.position;
+    let position = expected_result.position.in_file(file_path);
     let method_name = &expected_result.method_name;
     match expected_result.value {
         #[cfg(test)]
         ExpectedResultValue::Any => "".into(),
-        ExpectedResultValue::Ok => format!("in {file_path}:{position}"),
+        ExpectedResultValue::Ok => format!("in {position}"),
         ExpectedResultValue::NullOrVoid
         | ExpectedResultValue::NullOrVoidOrMethodNotFound
         | ExpectedResultValue::String(_)
+        | ExpectedResultValue::NotString(_)
+        | ExpectedResultValue::NoException
+        | ExpectedResultValue::Blank
+        | ExpectedResultValue::Error
+        | ExpectedResultValue::ErrorContaining(_)
+        | ExpectedResultValue::Throws(_)
+        | ExpectedResultValue::Comparison(_, _)
+        | ExpectedResultValue::Range { .. }
+        | ExpectedResultValue::Approximately(_)
+        | ExpectedResultValue::Matches(_)
+        | ExpectedResultValue::InvalidPattern { .. }
         | ExpectedResultValue::SetSymbol(_)
         | ExpectedResultValue::Symbol(_)
-        | ExpectedResultValue::List(_) => match method_name {
-            Some(method_name) => format!(
-                "in {file_path}:{position} for method call {}",
-                method_name.0
-            ),
-            None => format!("in {file_path}:{position}"),
+        | ExpectedResultValue::List(_)
+        | ExpectedResultValue::Table(_)
+        | ExpectedResultValue::OrderedTable(_)
+        | ExpectedResultValue::SubsetTable(_) => match method_name {
+            Some(method_name) => format!("in {position} for method call {}", method_name.0),
+            None => format!("in {position}"),
         },
     }
 }
 
+type QueryResultRow = Vec<(String, String)>;
+
+struct RowDiff<'a> {
+    missing: Vec<&'a ExpectedRow>,
+    surplus: Vec<&'a QueryResultRow>,
+    out_of_order: Vec<OutOfOrderRow<'a>>,
+}
+
+struct OutOfOrderRow<'a> {
+    row: &'a ExpectedRow,
+    expected_index: usize,
+    actual_index: usize,
+}
+
+impl RowDiff<'_> {
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.surplus.is_empty() && self.out_of_order.is_empty()
+    }
+}
+
+/// Converts the result of a `query` call, a list of rows where each row is a list of
+/// `[column, value]` pairs, into rows that can be matched against the markdown table.
+fn query_rows(value: &InstructionResultValue) -> Option<Vec<QueryResultRow>> {
+    let InstructionResultValue::List(rows) = value else {
+        return None;
+    };
+    rows.iter()
+        .map(|row| {
+            let InstructionResultValue::List(fields) = row else {
+                return None;
+            };
+            fields
+                .iter()
+                .map(|field| match field {
+                    InstructionResultValue::List(pair) => match pair.as_slice() {
+                        [InstructionResultValue::String(column), InstructionResultValue::String(value)] => {
+                            Some((column.clone(), value.clone()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn row_diff<'a>(
+    expected: &'a ExpectedResultValue,
+    actual_rows: &'a [QueryResultRow],
+) -> Option<RowDiff<'a>> {
+    match expected {
+        ExpectedResultValue::Table(expected_rows) => {
+            Some(unordered_row_diff(expected_rows, actual_rows))
+        }
+        ExpectedResultValue::OrderedTable(expected_rows) => {
+            Some(ordered_row_diff(expected_rows, actual_rows))
+        }
+        ExpectedResultValue::SubsetTable(expected_rows) => {
+            Some(subset_row_diff(expected_rows, actual_rows))
+        }
+        _ => None,
+    }
+}
+
+fn row_matches(expected: &ExpectedRow, actual: &QueryResultRow) -> bool {
+    expected.cells.iter().all(|(column, value)| {
+        actual.iter().any(|(actual_column, actual_value)| {
+            actual_column == column
+                && *value == InstructionResultValue::String(actual_value.clone())
+        })
+    })
+}
+
+/// For each expected row, the index of the first returned row that matches it and was not
+/// matched by a previous expected row.
+fn match_rows(expected_rows: &[ExpectedRow], actual_rows: &[QueryResultRow]) -> Vec<Option<usize>> {
+    let mut taken = vec![false; actual_rows.len()];
+    expected_rows
+        .iter()
+        .map(|expected| {
+            let index = actual_rows
+                .iter()
+                .enumerate()
+                .position(|(i, actual)| !taken[i] && row_matches(expected, actual))?;
+            taken[index] = true;
+            Some(index)
+        })
+        .collect()
+}
+
+fn missing_rows<'a>(
+    expected_rows: &'a [ExpectedRow],
+    matches: &[Option<usize>],
+) -> Vec<&'a ExpectedRow> {
+    expected_rows
+        .iter()
+        .zip(matches)
+        .filter(|(_, matched)| matched.is_none())
+        .map(|(row, _)| row)
+        .collect()
+}
+
+fn surplus_rows<'a>(
+    actual_rows: &'a [QueryResultRow],
+    matches: &[Option<usize>],
+) -> Vec<&'a QueryResultRow> {
+    actual_rows
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matches.contains(&Some(*i)))
+        .map(|(_, row)| row)
+        .collect()
+}
+
+fn unordered_row_diff<'a>(
+    expected_rows: &'a [ExpectedRow],
+    actual_rows: &'a [QueryResultRow],
+) -> RowDiff<'a> {
+    let matches = match_rows(expected_rows, actual_rows);
+    RowDiff {
+        missing: missing_rows(expected_rows, &matches),
+        surplus: surplus_rows(actual_rows, &matches),
+        out_of_order: Vec::new(),
+    }
+}
+
+fn subset_row_diff<'a>(
+    expected_rows: &'a [ExpectedRow],
+    actual_rows: &'a [QueryResultRow],
+) -> RowDiff<'a> {
+    let matches = match_rows(expected_rows, actual_rows);
+    RowDiff {
+        missing: missing_rows(expected_rows, &matches),
+        surplus: Vec::new(),
+        out_of_order: Vec::new(),
+    }
+}
+
+/// Rows that are present but out of order are the ones left out of the longest sequence of
+/// matched rows that already come back in the expected order.
+fn ordered_row_diff<'a>(
+    expected_rows: &'a [ExpectedRow],
+    actual_rows: &'a [QueryResultRow],
+) -> RowDiff<'a> {
+    let matches = match_rows(expected_rows, actual_rows);
+    let matched: Vec<(usize, usize)> = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(expected_index, actual_index)| Some((expected_index, (*actual_index)?)))
+        .collect();
+    let mut lengths = vec![1; matched.len()];
+    let mut previous = vec![None; matched.len()];
+    for i in 0..matched.len() {
+        for j in 0..i {
+            if matched[j].1 < matched[i].1 && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut in_order = vec![false; matched.len()];
+    let mut current = (0..matched.len()).max_by_key(|i| lengths[*i]);
+    while let Some(i) = current {
+        in_order[i] = true;
+        current = previous[i];
+    }
+    RowDiff {
+        missing: missing_rows(expected_rows, &matches),
+        surplus: surplus_rows(actual_rows, &matches),
+        out_of_order: matched
+            .into_iter()
+            .zip(in_order)
+            .filter(|(_, in_order)| !in_order)
+            .map(|((expected_index, actual_index), _)| OutOfOrderRow {
+                row: &expected_rows[expected_index],
+                expected_index,
+                actual_index,
+            })
+            .collect(),
+    }
+}
+
+fn row_diff_failures(
+    file_path: &str,
+    expected_result: &ExpectedResult,
+    diff: RowDiff,
+) -> Vec<Failure> {
+    let method_call = expected_result
+        .method_name
+        .as_ref()
+        .map(|method_name| format!(" for method call {}", method_name.0))
+        .unwrap_or_default();
+    let mut failures = Vec::new();
+    for row in diff.missing {
+        failures.push(Failure {
+            position: row.position.clone(),
+            message: format!(
+                "Missing row {row} in {}{method_call}",
+                row.position.in_file(file_path)
+            ),
+        });
+    }
+    for OutOfOrderRow {
+        row,
+        expected_index,
+        actual_index,
+    } in diff.out_of_order
+    {
+        failures.push(Failure {
+            position: row.position.clone(),
+            message: format!(
+                "Out of order row {row}. Expected at index {expected_index}, got at index {actual_index} in {}{method_call}",
+                row.position.in_file(file_path)
+            ),
+        });
+    }
+    for row in diff.surplus {
+        failures.push(Failure {
+            position: expected_result.position.clone(),
+            message: format!(
+                "Surplus row {} in {}{method_call}",
+                query_row_to_string(row),
+                expected_result.position.in_file(file_path)
+            ),
+        });
+    }
+    failures
+}
+
+fn query_row_to_string(row: &QueryResultRow) -> String {
+    format!(
+        "[{}]",
+        row.iter()
+            .map(|(column, value)| format!("{column}=`{value}`"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
 #[cfg(test)]
 mod test {
     use crate::processor::markdown_commands::{MethodName, Position};
@@ -148,9 +553,27 @@ mod test {
 
     use super::*;
 
+    fn validate_result_failures(
+        file_path: impl Display,
+        expected_result: Vec<(ExpectedResult, Snooze)>,
+        result: Vec<InstructionResult>,
+        state: &mut State,
+    ) -> Result<Vec<(String, Snooze)>> {
+        Ok(validate_result(file_path, expected_result, result, state)?
+            .into_iter()
+            .flat_map(|checked_result| {
+                let snooze = checked_result.snooze;
+                checked_result
+                    .failures
+                    .into_iter()
+                    .map(move |failure| (failure.message, snooze.clone()))
+            })
+            .collect())
+    }
+
     #[test]
     fn incorrect_number_of_results() -> Result<()> {
-        let result = validate_result(
+        let result = validate_result_failures(
             "file_path.md",
             vec![],
             vec![InstructionResult::ok(Id::new())],
@@ -168,7 +591,7 @@ mod test {
         let id = Id::new();
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_path.md",
             vec![
                 (
@@ -305,7 +728,7 @@ mod test {
         let id_2 = Id::new();
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_file.md",
             vec![
                 (
@@ -480,7 +903,7 @@ mod test {
         assert_eq!(
             vec![
                 (format!("Different ID in response. Expected {id_1} but got {id_2}",), Snooze::not_snooze()),
-                (format!("Expected NULL or VOID or NOT FOUND, got OK in test_file.md:0:0",), Snooze::not_snooze()),
+                ("Expected NULL or VOID or NOT FOUND, got OK in test_file.md:0:0".to_string(), Snooze::not_snooze()),
                 (format!("Different ID in response. Expected {id_1} but got {id_2}",), Snooze::not_snooze()),
                 (format!(
                     "Expected NULL or VOID, got OK in test_file.md:{position} for method call TestMethod"
@@ -531,7 +954,7 @@ mod test {
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
         let mut state = State::default();
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_file.md",
             vec![(
                 ExpectedResult::set_symbol(
@@ -556,7 +979,7 @@ mod test {
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
         let mut state = State::default();
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_file.md",
             vec![
                 (
@@ -596,9 +1019,9 @@ mod test {
         )?;
         assert_eq!(
             vec![
-                (format!("Expected SET SYMBOL `Symbol`, got OK in test_file.md:0:0 for method call TestMethod"), Snooze::not_snooze()),
-                (format!("Expected SET SYMBOL `Symbol`, got VOID in test_file.md:0:0 for method call TestMethod"), Snooze::not_snooze()),
-                (format!("Expected SET SYMBOL `Symbol`, got Exception `Error` in test_file.md:0:0 for method call TestMethod"), Snooze::not_snooze()),
+                ("Expected SET SYMBOL `Symbol`, got OK in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected SET SYMBOL `Symbol`, got VOID in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected SET SYMBOL `Symbol`, got Exception `Error` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
             ],
             result
         );
@@ -611,7 +1034,7 @@ mod test {
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
         let mut state = State::default();
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_file.md",
             vec![(
                 ExpectedResult::symbol(
@@ -626,8 +1049,11 @@ mod test {
             &mut state,
         );
         assert_eq!(
-            "Symbol `Symbol` not found",
-            result.expect_err("Expect error").to_string()
+            vec![(
+                "Undefined symbol `Symbol` in test_file.md:0:0".to_string(),
+                Snooze::not_snooze()
+            )],
+            result?
         );
         Ok(())
     }
@@ -638,7 +1064,7 @@ mod test {
         let position = Position::new(0, 0);
         let method_name = MethodName("TestMethod".into(), position.clone());
         let mut state = State::default();
-        let result = validate_result(
+        let result = validate_result_failures(
             "test_file.md",
             vec![
                 (
@@ -669,4 +1095,588 @@ mod test {
         assert!(result.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_script_expectations() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(0, 0);
+        let method_name = MethodName("TestMethod".into(), position.clone());
+        let not_false = || {
+            ExpectedResult::not_string(
+                id.clone(),
+                position.clone(),
+                method_name.clone(),
+                "false".into(),
+            )
+        };
+        let no_exception =
+            || ExpectedResult::no_exception(id.clone(), position.clone(), method_name.clone());
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                (not_false(), Snooze::not_snooze()),
+                (not_false(), Snooze::not_snooze()),
+                (not_false(), Snooze::not_snooze()),
+                (not_false(), Snooze::not_snooze()),
+                (not_false(), Snooze::not_snooze()),
+                (no_exception(), Snooze::not_snooze()),
+                (no_exception(), Snooze::not_snooze()),
+                (no_exception(), Snooze::not_snooze()),
+            ],
+            vec![
+                InstructionResult::void(id.clone()),
+                InstructionResult::ok(id.clone()),
+                InstructionResult::string(id.clone(), "true".into()),
+                InstructionResult::string(id.clone(), "false".into()),
+                InstructionResult::exception(id.clone(), ExceptionMessage::new("Error".into())),
+                InstructionResult::string(id.clone(), "false".into()),
+                InstructionResult::void(id.clone()),
+                InstructionResult::exception(id.clone(), ExceptionMessage::new("Error".into())),
+            ],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![
+                ("Expected NOT `false`, got `false` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected NOT `false`, got Exception `Error` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected NO EXCEPTION, got Exception `Error` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exception_expectations() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(0, 0);
+        let method_name = MethodName("TestMethod".into(), position.clone());
+        let cell = |value: &str| {
+            (
+                ExpectedResult::cell(
+                    id.clone(),
+                    position.clone(),
+                    method_name.clone(),
+                    value.into(),
+                ),
+                Snooze::not_snooze(),
+            )
+        };
+        let invalid_amount = || {
+            InstructionResult::exception(
+                id.clone(),
+                ExceptionMessage::new(
+                    "java.lang.IllegalArgumentException: message:<<Invalid amount>>".into(),
+                ),
+            )
+        };
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                cell("error"),
+                cell("error: Invalid"),
+                cell("throws IllegalArgumentException"),
+                cell("throws java.lang.IllegalArgumentException"),
+                cell("error"),
+                cell("error: Negative"),
+                cell("throws IllegalStateException"),
+            ],
+            vec![
+                invalid_amount(),
+                invalid_amount(),
+                invalid_amount(),
+                invalid_amount(),
+                InstructionResult::string(id.clone(), "10".into()),
+                invalid_amount(),
+                invalid_amount(),
+            ],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![
+                ("Expected ERROR, got `10` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected ERROR `Negative`, got Exception `Invalid amount` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                ("Expected THROWS `IllegalStateException`, got Exception `Invalid amount` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn quoted_cells_are_literal() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(0, 0);
+        let method_name = MethodName("TestMethod".into(), position.clone());
+        let cell = |value: &str| {
+            (
+                ExpectedResult::cell(
+                    id.clone(),
+                    position.clone(),
+                    method_name.clone(),
+                    value.into(),
+                ),
+                Snooze::not_snooze(),
+            )
+        };
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                cell("\"error\""),
+                cell("\"throws Exception\""),
+                cell("\"< 5\""),
+                cell("\"\""),
+                cell("\"error\""),
+            ],
+            vec![
+                InstructionResult::string(id.clone(), "error".into()),
+                InstructionResult::string(id.clone(), "throws Exception".into()),
+                InstructionResult::string(id.clone(), "< 5".into()),
+                InstructionResult::string(id.clone(), "".into()),
+                InstructionResult::exception(id.clone(), ExceptionMessage::new("Invalid".into())),
+            ],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![(
+                "Expected `error`, got Exception `Invalid` in test_file.md:0:0 for method call TestMethod".to_string(),
+                Snooze::not_snooze()
+            )],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cell_expressions() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(0, 0);
+        let method_name = MethodName("TestMethod".into(), position.clone());
+        let cells = [
+            ("< 5", "4"),
+            ("<= 5", "5.0"),
+            ("> 5", "5"),
+            (">= 10", "10"),
+            ("!= 0", "0.0"),
+            ("!= 0", "1"),
+            ("3 < _ <= 7", "7"),
+            ("3 < _ <= 7", "3"),
+            ("~= 3.14", "3.14159"),
+            ("~= 3.14", "3.15"),
+            ("~= 1", "0.6"),
+            ("< 5", "abc"),
+            ("=~/^order-\\d+$/", "order-42"),
+            ("=~/^order-\\d+$/", "invoice-42"),
+            ("", "42"),
+            ("\"\"", ""),
+            ("(blank)", "42"),
+            ("=~/(/", "("),
+        ];
+        let result = validate_result_failures(
+            "test_file.md",
+            cells
+                .iter()
+                .map(|(cell, _)| {
+                    (
+                        ExpectedResult::cell(
+                            id.clone(),
+                            position.clone(),
+                            method_name.clone(),
+                            cell.to_string(),
+                        ),
+                        Snooze::not_snooze(),
+                    )
+                })
+                .collect(),
+            cells
+                .iter()
+                .map(|(_, actual)| InstructionResult::string(id.clone(), actual.to_string()))
+                .collect(),
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![
+                (
+                    "Expected `> 5`, got `5` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Expected `!= 0`, got `0.0` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Expected `3 < _ <= 7`, got `3` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Expected `~= 3.14`, got `3.15` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Expected `< 5`, got `abc` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                ("Expected =~/^order-\\d+$/, got `invoice-42` in test_file.md:0:0 for method call TestMethod".to_string(), Snooze::not_snooze()),
+                (
+                    "Expected ``, got `42` in test_file.md:0:0 for method call TestMethod"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Invalid regular expression `(` in test_file.md:0:0: regex parse error:\n    (\n    ^\nerror: unclosed group"
+                        .to_string(),
+                    Snooze::not_snooze()
+                ),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_table() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(1, 1);
+        let method_name = MethodName("query".into(), position.clone());
+        let row = |line: usize, cells: &[(&str, &str)]| ExpectedRow {
+            position: Position::new(line, 1),
+            cells: cells
+                .iter()
+                .map(|(column, value)| {
+                    (
+                        column.to_string(),
+                        ExpectedResultValue::cell(value.to_string()),
+                    )
+                })
+                .collect(),
+        };
+        let actual_row = |cells: &[(&str, &str)]| {
+            InstructionResultValue::List(
+                cells
+                    .iter()
+                    .map(|(column, value)| {
+                        InstructionResultValue::List(vec![
+                            InstructionResultValue::String(column.to_string()),
+                            InstructionResultValue::String(value.to_string()),
+                        ])
+                    })
+                    .collect(),
+            )
+        };
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                (
+                    ExpectedResult::table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        vec![
+                            row(3, &[("name", "bob"), ("age", "30")]),
+                            row(4, &[("name", "alice")]),
+                        ],
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        vec![
+                            row(3, &[("name", "bob"), ("age", "30")]),
+                            row(4, &[("name", "alice")]),
+                        ],
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        vec![row(3, &[("name", "bob")])],
+                    ),
+                    Snooze::not_snooze(),
+                ),
+            ],
+            vec![
+                InstructionResult::list(
+                    id.clone(),
+                    vec![
+                        actual_row(&[("name", "alice"), ("age", "25")]),
+                        actual_row(&[("name", "bob"), ("age", "30")]),
+                    ],
+                ),
+                InstructionResult::list(
+                    id.clone(),
+                    vec![
+                        actual_row(&[("name", "bob"), ("age", "31")]),
+                        actual_row(&[("name", "alice"), ("age", "25")]),
+                        actual_row(&[("name", "carol"), ("age", "40")]),
+                    ],
+                ),
+                InstructionResult::string(id.clone(), "Value".into()),
+            ],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![
+                (
+                    "Missing row [name=`bob`, age=`30`] in test_file.md:3:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Surplus row [name=`bob`, age=`31`] in test_file.md:1:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Surplus row [name=`carol`, age=`40`] in test_file.md:1:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Expected [[name=`bob`]], got `Value` in test_file.md:1:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_and_subset_query_tables() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(1, 1);
+        let method_name = MethodName("query".into(), position.clone());
+        let rows = || {
+            ["bob", "alice", "carol"]
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| ExpectedRow {
+                    position: Position::new(i + 3, 1),
+                    cells: vec![("name".into(), ExpectedResultValue::String(name.into()))],
+                })
+                .collect::<Vec<ExpectedRow>>()
+        };
+        let actual = |names: &[&str]| {
+            InstructionResult::list(
+                id.clone(),
+                names
+                    .iter()
+                    .map(|name| {
+                        InstructionResultValue::List(vec![InstructionResultValue::List(vec![
+                            InstructionResultValue::String("name".into()),
+                            InstructionResultValue::String(name.to_string()),
+                        ])])
+                    })
+                    .collect(),
+            )
+        };
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                (
+                    ExpectedResult::ordered_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        rows(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::ordered_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        rows(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::subset_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        rows(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::subset_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        rows(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+            ],
+            vec![
+                actual(&["bob", "alice", "carol"]),
+                actual(&["alice", "bob", "dave", "carol"]),
+                actual(&["dave", "carol", "alice", "bob"]),
+                actual(&["carol", "alice"]),
+            ],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![
+                (
+                    "Out of order row [name=`alice`]. Expected at index 1, got at index 0 in test_file.md:4:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Surplus row [name=`dave`] in test_file.md:1:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+                (
+                    "Missing row [name=`bob`] in test_file.md:3:1 for method call query".to_string(),
+                    Snooze::not_snooze()
+                ),
+            ],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn query_cells_match_like_decision_table_cells() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(1, 1);
+        let method_name = MethodName("query".into(), position.clone());
+        let row = |line: usize, name: &str, age: &str| ExpectedRow {
+            position: Position::new(line, 1),
+            cells: vec![
+                ("name".into(), ExpectedResultValue::cell(name.into())),
+                ("age".into(), ExpectedResultValue::cell(age.into())),
+            ],
+        };
+        let actual = || {
+            InstructionResult::list(
+                id.clone(),
+                [("bob", "30"), ("alice", "25")]
+                    .iter()
+                    .map(|(name, age)| {
+                        InstructionResultValue::List(vec![
+                            InstructionResultValue::List(vec![
+                                InstructionResultValue::String("name".into()),
+                                InstructionResultValue::String(name.to_string()),
+                            ]),
+                            InstructionResultValue::List(vec![
+                                InstructionResultValue::String("age".into()),
+                                InstructionResultValue::String(age.to_string()),
+                            ]),
+                        ])
+                    })
+                    .collect(),
+            )
+        };
+        let result = validate_result_failures(
+            "test_file.md",
+            vec![
+                (
+                    ExpectedResult::ordered_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        vec![row(3, "=~/^b/", "> 18"), row(4, "alice", "")],
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::subset_table(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        vec![row(3, "\"bob\"", "< 18")],
+                    ),
+                    Snooze::not_snooze(),
+                ),
+            ],
+            vec![actual(), actual()],
+            &mut State::default(),
+        )?;
+        assert_eq!(
+            vec![(
+                "Missing row [name=`bob`, age=`< 18`] in test_file.md:3:1 for method call query"
+                    .to_string(),
+                Snooze::not_snooze()
+            )],
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn checked_results() -> Result<()> {
+        let id = Id::new();
+        let position = Position::new(3, 5);
+        let method_name = MethodName("sum".into(), position.clone());
+        let mut state = State::default();
+        state.set_symbol("Symbol".into(), "3".into());
+        let result = validate_result(
+            "test_file.md",
+            vec![
+                (
+                    ExpectedResult::symbol(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        "Symbol".into(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+                (
+                    ExpectedResult::string(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        "3".into(),
+                    ),
+                    Snooze::not_snooze(),
+                ),
+            ],
+            vec![
+                InstructionResult::string(id.clone(), "3".into()),
+                InstructionResult::string(id.clone(), "4".into()),
+            ],
+            &mut state,
+        )?;
+        assert_eq!(
+            vec![
+                CheckedResult {
+                    expected: ExpectedResult::string(
+                        id.clone(),
+                        position.clone(),
+                        method_name.clone(),
+                        "3".into()
+                    ),
+                    actual: InstructionResultValue::String("3".into()),
+                    snooze: Snooze::not_snooze(),
+                    failures: vec![],
+                },
+                CheckedResult {
+                    expected: ExpectedResult::string(id, position, method_name, "3".into()),
+                    actual: InstructionResultValue::String("4".into()),
+                    snooze: Snooze::not_snooze(),
+                    failures: vec![Failure {
+                        position: Position::new(3, 5),
+                        message: "Expected `3`, got `4` in test_file.md:3:5 for method call sum"
+                            .into()
+                    }],
+                },
+            ],
+            result
+        );
+        assert!(result[0].passed());
+        assert!(!result[1].passed());
+        Ok(())
+    }
 }
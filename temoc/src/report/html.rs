@@ -0,0 +1,436 @@
+use super::{escape, TestSuiteResult};
+use crate::processor::{CheckedResult, ExpectedResultValue, Position};
+use anyhow::{anyhow, Result};
+use markdown::mdast::{Node, Table, TableCell};
+use markdown::{to_html_with_options, to_mdast, Options, ParseOptions};
+use slim_protocol::InstructionResultValue;
+use std::fmt::Write;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #999; padding: 4px 8px; text-align: left; }
+.pass { background-color: #cfffcf; }
+.fail { background-color: #ffcfcf; }
+.snoozed { background-color: #ffffcf; }
+.ignored { background-color: #efefef; }
+.directive { font-family: monospace; color: #555; }
+.actual { display: block; font-style: italic; }
+.actual::before { content: \"actual: \"; }";
+
+/// Writes an annotated page for each tested file and an index with the totals of each of them.
+pub fn html(dir: &Path, test_suites: &[TestSuiteResult]) -> Result<()> {
+    create_dir_all(dir)?;
+    for test_suite in test_suites {
+        let source = read_to_string(&test_suite.name)?;
+        write(
+            dir.join(page_name(&test_suite.name)),
+            page(test_suite, &source)?,
+        )?;
+    }
+    write(dir.join("index.html"), index(test_suites)?)?;
+    Ok(())
+}
+
+fn page_name(file: &str) -> String {
+    format!("{}.html", file.replace(['/', '\\', ':'], "_"))
+}
+
+/// Ordered from the least to the most relevant, so a cell shows the worst of its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Ignored,
+    Pass,
+    Snoozed,
+    Fail,
+}
+
+impl Status {
+    fn class(self) -> &'static str {
+        match self {
+            Status::Ignored => "ignored",
+            Status::Pass => "pass",
+            Status::Snoozed => "snoozed",
+            Status::Fail => "fail",
+        }
+    }
+
+    fn of_test_suite(test_suite: &TestSuiteResult) -> Self {
+        if test_suite.failures() > 0 {
+            Status::Fail
+        } else if test_suite.snoozed() + test_suite.skipped() > 0 {
+            Status::Snoozed
+        } else {
+            Status::Pass
+        }
+    }
+}
+
+/// The status of a result at a position of the tested file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Annotation {
+    line: usize,
+    column: usize,
+    status: Status,
+    actual: Option<String>,
+    messages: Vec<String>,
+}
+
+fn annotations(test_suite: &TestSuiteResult) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    for test_case in &test_suite.test_cases {
+        if test_case.file != test_suite.name {
+            continue;
+        }
+        for result in &test_case.results {
+            annotations.extend(annotation(result, &result.expected.position, true));
+            if let ExpectedResultValue::Table(rows)
+            | ExpectedResultValue::OrderedTable(rows)
+            | ExpectedResultValue::SubsetTable(rows) = &result.expected.value
+            {
+                // Without a list of rows the failure of the query call itself covers every row.
+                let rows_returned = matches!(result.actual, InstructionResultValue::List(_));
+                annotations.extend(
+                    rows.iter()
+                        .filter_map(|row| annotation(result, &row.position, !rows_returned)),
+                );
+            }
+        }
+    }
+    annotations
+}
+
+/// The annotation of the failures of the result at the position, or of the result itself when
+/// `include_result` is set.
+fn annotation(
+    result: &CheckedResult,
+    position: &Position,
+    include_result: bool,
+) -> Option<Annotation> {
+    let origin = position.origin();
+    if origin.is_included() {
+        return None;
+    }
+    let messages: Vec<String> = result
+        .failures
+        .iter()
+        .filter(|failure| {
+            &failure.position == position
+                || (include_result && failure.position == result.expected.position)
+        })
+        .map(|failure| failure.message.clone())
+        .collect();
+    let status = match (messages.is_empty(), result.snooze.should_snooze()) {
+        (true, _) => Status::Pass,
+        (false, true) => Status::Snoozed,
+        (false, false) => Status::Fail,
+    };
+    Some(Annotation {
+        line: origin.line(),
+        column: origin.column(),
+        status,
+        // A pattern doesn't say which value matched it and a blank cell has no value at all, so the
+        // actual one is shown even when they pass.
+        actual: ((status != Status::Pass
+            || matches!(
+                result.expected.value,
+                ExpectedResultValue::Matches(_) | ExpectedResultValue::Blank
+            ))
+            && position == &result.expected.position)
+            .then(|| match &result.actual {
+                InstructionResultValue::String(value) => value.clone(),
+                actual => actual.to_string(),
+            }),
+        messages,
+    })
+}
+
+fn page(test_suite: &TestSuiteResult, source: &str) -> Result<String> {
+    let markdown = to_mdast(source, &ParseOptions::gfm())
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))?;
+    let annotations = annotations(test_suite);
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>",
+        escape(&test_suite.name)
+    )?;
+    writeln!(
+        html,
+        "<p><a href=\"index.html\">Index</a></p>\n<h1 class=\"{}\">{}</h1>",
+        Status::of_test_suite(test_suite).class(),
+        escape(&test_suite.name)
+    )?;
+    let failures: Vec<(String, Status)> = test_suite
+        .test_cases
+        .iter()
+        .flat_map(|test_case| test_case.failures())
+        .map(|(failure, snooze)| {
+            let status = if snooze.should_snooze() {
+                Status::Snoozed
+            } else {
+                Status::Fail
+            };
+            (failure, status)
+        })
+        .collect();
+    if !failures.is_empty() {
+        html.push_str("<ul>\n");
+        for (failure, status) in failures {
+            writeln!(
+                html,
+                "<li class=\"{}\">{}</li>",
+                status.class(),
+                escape(&failure)
+            )?;
+        }
+        html.push_str("</ul>\n");
+    }
+    for node in markdown.children().into_iter().flatten() {
+        match node {
+            Node::Table(table) => html.push_str(&table_html(table, source, &annotations)?),
+            Node::Definition(definition)
+                if definition.url == "#" && definition.identifier == "//" =>
+            {
+                let (Some(directive), Some(position)) = (&definition.title, &definition.position)
+                else {
+                    continue;
+                };
+                let status = annotations
+                    .iter()
+                    .filter(|annotation| {
+                        annotation.line == position.start.line
+                            && annotation.column == position.start.column
+                    })
+                    .map(|annotation| annotation.status)
+                    .max()
+                    .map(|status| format!(" {}", status.class()))
+                    .unwrap_or_default();
+                writeln!(
+                    html,
+                    "<p class=\"directive{status}\">{}</p>",
+                    escape(directive)
+                )?;
+            }
+            node => html.push_str(&markdown_html(source, node)?),
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+fn markdown_html(source: &str, node: &Node) -> Result<String> {
+    let Some(position) = node.position() else {
+        return Ok(String::new());
+    };
+    to_html_with_options(
+        &source[position.start.offset..position.end.offset],
+        &Options::gfm(),
+    )
+    .map_err(|err| anyhow!("Error rendering markdown {err}"))
+}
+
+fn table_html(table: &Table, source: &str, annotations: &[Annotation]) -> Result<String> {
+    let mut html = String::from("<table>\n");
+    // Columns starting with `#` are comments and never have results.
+    let comment_columns: Vec<usize> = match table.children.first() {
+        Some(Node::TableRow(header)) => header
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| {
+                cell.children()
+                    .map(|children| cell_text(source, children).starts_with('#'))
+                    .unwrap_or(false)
+            })
+            .map(|(column, _)| column)
+            .collect(),
+        _ => vec![],
+    };
+    for (index, row) in table.children.iter().enumerate() {
+        let (Node::TableRow(row), Some(row_position)) = (row, row.position()) else {
+            continue;
+        };
+        let line = row_position.start.line;
+        let row_annotations: Vec<&Annotation> = annotations
+            .iter()
+            .filter(|annotation| {
+                annotation.line == line && annotation.column == row_position.start.column
+            })
+            .collect();
+        let cells: Vec<(&TableCell, Vec<&Annotation>)> = row
+            .children
+            .iter()
+            .filter_map(|cell| match cell {
+                Node::TableCell(cell) => Some(cell),
+                _ => None,
+            })
+            .map(|cell| {
+                let cell_annotations = cell
+                    .position
+                    .as_ref()
+                    .map(|cell_position| {
+                        annotations
+                            .iter()
+                            .filter(|annotation| {
+                                annotation.line == line
+                                    && annotation.column != row_position.start.column
+                                    && annotation.column >= cell_position.start.column
+                                    && annotation.column < cell_position.end.column
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (cell, cell_annotations)
+            })
+            .collect();
+        html.push_str("<tr>");
+        for (column, (cell, mut cell_annotations)) in cells.into_iter().enumerate() {
+            if comment_columns.contains(&column) {
+                cell_annotations.clear();
+            } else {
+                cell_annotations.extend(row_annotations.iter().copied());
+            }
+            let status = cell_annotations
+                .iter()
+                .map(|annotation| annotation.status)
+                .max()
+                .or((index > 0).then_some(Status::Ignored));
+            let tag = if index == 0 { "th" } else { "td" };
+            let class = status
+                .map(|status| format!(" class=\"{}\"", status.class()))
+                .unwrap_or_default();
+            let messages: Vec<&str> = cell_annotations
+                .iter()
+                .flat_map(|annotation| annotation.messages.iter().map(String::as_str))
+                .collect();
+            let title = if messages.is_empty() {
+                String::new()
+            } else {
+                format!(" title=\"{}\"", escape(&messages.join("\n")))
+            };
+            write!(
+                html,
+                "<{tag}{class}{title}>{}",
+                cell_text(source, &cell.children)
+            )?;
+            for actual in cell_annotations
+                .iter()
+                .filter_map(|annotation| annotation.actual.as_ref())
+            {
+                write!(html, "<span class=\"actual\">{}</span>", escape(actual))?;
+            }
+            write!(html, "</{tag}>")?;
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    Ok(html)
+}
+
+/// The text of the cell as written in the markdown.
+fn cell_text(source: &str, children: &[Node]) -> String {
+    match (
+        children.first().and_then(Node::position),
+        children.last().and_then(Node::position),
+    ) {
+        (Some(first), Some(last)) => escape(&source[first.start.offset..last.end.offset]),
+        _ => String::new(),
+    }
+}
+
+fn index(test_suites: &[TestSuiteResult]) -> Result<String> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Test results</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Test results</h1>\n<table>"
+    )?;
+    html.push_str(
+        "<tr><th>File</th><th>Tests</th><th>Passed</th><th>Failed</th><th>Snoozed</th><th>Skipped</th><th>Time</th></tr>\n",
+    );
+    for test_suite in test_suites {
+        let tests = test_suite.test_cases.len();
+        let failures = test_suite.failures();
+        let snoozed = test_suite.snoozed();
+        let skipped = test_suite.skipped();
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td><a href=\"{}\">{}</a></td><td>{tests}</td><td>{}</td><td>{failures}</td><td>{snoozed}</td><td>{skipped}</td><td>{:.3}s</td></tr>",
+            Status::of_test_suite(test_suite).class(),
+            escape(&page_name(&test_suite.name)),
+            escape(&test_suite.name),
+            tests - failures - snoozed - skipped,
+            test_suite.time.as_secs_f64(),
+        )?;
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    Ok(html)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::{ExpectedResult, Failure, Snooze, TestCaseResult};
+    use slim_protocol::Id;
+    use std::time::Duration;
+
+    fn checked_result(line: usize, column: usize, failure: Option<&str>) -> CheckedResult {
+        let position = Position::new(line, column);
+        CheckedResult {
+            expected: ExpectedResult::null_or_void(Id::new(), position.clone(), None),
+            actual: InstructionResultValue::String("4".into()),
+            snooze: Snooze::not_snooze(),
+            failures: failure
+                .map(|message| Failure {
+                    position,
+                    message: message.into(),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn matches_pattern(line: usize, column: usize, cell: &str) -> CheckedResult {
+        let mut result = checked_result(line, column, None);
+        result.expected.value = ExpectedResultValue::cell(cell.into());
+        result
+    }
+
+    #[test]
+    fn annotated_table() -> Result<()> {
+        let source = "| a | # note | sum? |\n| --- | --- | --- |\n| 1 | x | 3 |\n| 2 | y | =~/4/ |\n| 3 | z | 5 |\n";
+        let test_suite = TestSuiteResult {
+            name: "calculator.md".into(),
+            test_cases: vec![TestCaseResult {
+                name: "Calculator".into(),
+                file: "calculator.md".into(),
+                position: Position::new(1, 1),
+                results: vec![
+                    checked_result(3, 1, None),
+                    checked_result(3, 3, None),
+                    checked_result(3, 11, Some("Expected `3`, got `4`")),
+                    matches_pattern(4, 11, "=~/4/"),
+                ],
+                skipped: None,
+            }],
+            time: Duration::ZERO,
+        };
+        let markdown = to_mdast(source, &ParseOptions::gfm()).unwrap();
+        let Some(Node::Table(table)) = markdown.children().and_then(|children| children.first())
+        else {
+            panic!("Expected a table");
+        };
+        assert_eq!(
+            "<table>
+<tr><th>a</th><th># note</th><th>sum?</th></tr>
+<tr><td class=\"pass\">1</td><td class=\"ignored\">x</td><td class=\"fail\" title=\"Expected `3`, got `4`\">3<span class=\"actual\">4</span></td></tr>
+<tr><td class=\"ignored\">2</td><td class=\"ignored\">y</td><td class=\"pass\">=~/4/<span class=\"actual\">4</span></td></tr>
+<tr><td class=\"ignored\">3</td><td class=\"ignored\">z</td><td class=\"ignored\">5</td></tr>
+</table>
+",
+            table_html(table, source, &annotations(&test_suite))?
+        );
+        Ok(())
+    }
+}
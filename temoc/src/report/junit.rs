@@ -0,0 +1,168 @@
+use super::{escape, TestSuiteResult};
+use anyhow::Result;
+use std::fmt::Write;
+
+pub fn junit(test_suites: &[TestSuiteResult]) -> Result<String> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuites tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+        test_suites
+            .iter()
+            .map(|test_suite| test_suite.test_cases.len())
+            .sum::<usize>(),
+        test_suites
+            .iter()
+            .map(TestSuiteResult::failures)
+            .sum::<usize>(),
+        test_suites
+            .iter()
+            .map(|test_suite| test_suite.snoozed() + test_suite.skipped())
+            .sum::<usize>(),
+    )?;
+    for test_suite in test_suites {
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            escape(&test_suite.name),
+            test_suite.test_cases.len(),
+            test_suite.failures(),
+            test_suite.snoozed() + test_suite.skipped(),
+            test_suite.time.as_secs_f64(),
+        )?;
+        for test_case in &test_suite.test_cases {
+            write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape(&format!("{} ({})", test_case.name, test_case.location())),
+                escape(&test_suite.name),
+            )?;
+            let failures = test_case.failures();
+            let messages = |snoozed: bool| {
+                failures
+                    .iter()
+                    .filter(|(_, snooze)| snooze.should_snooze() == snoozed)
+                    .map(|(failure, _)| failure.as_str())
+                    .collect::<Vec<&str>>()
+            };
+            if test_case.failed() {
+                let failures = messages(false);
+                write!(
+                    xml,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape(failures[0]),
+                    escape(&failures.join("\n")),
+                )?;
+            } else if let Some(reason) = &test_case.skipped {
+                writeln!(
+                    xml,
+                    ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                    escape(reason)
+                )?;
+            } else if let Some((_, snooze)) = failures.first() {
+                write!(
+                    xml,
+                    ">\n      <skipped message=\"snoozed until {snooze}\">{}</skipped>\n    </testcase>\n",
+                    escape(&messages(true).join("\n")),
+                )?;
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::{
+        CheckedResult, ExpectedResult, Failure, Position, Snooze, TestCaseResult,
+    };
+    use chrono::NaiveDate;
+    use slim_protocol::{Id, InstructionResultValue};
+    use std::time::Duration;
+
+    fn test_case(line: usize, failures: Vec<(&str, Snooze)>) -> TestCaseResult {
+        TestCaseResult {
+            name: "Calculator".into(),
+            file: "calculator.md".into(),
+            position: Position::new(line, 1),
+            results: failures
+                .into_iter()
+                .map(|(failure, snooze)| CheckedResult {
+                    expected: ExpectedResult::ok(Id::new(), Position::new(line + 2, 1)),
+                    actual: InstructionResultValue::Void,
+                    snooze,
+                    failures: vec![Failure {
+                        position: Position::new(line + 2, 1),
+                        message: failure.into(),
+                    }],
+                })
+                .collect(),
+            skipped: None,
+        }
+    }
+
+    #[test]
+    fn junit_report() -> Result<()> {
+        let snoozed = Snooze::snooze(NaiveDate::from_ymd_opt(2099, 12, 31).unwrap());
+        let test_suites = vec![
+            TestSuiteResult {
+                name: "calculator.md".into(),
+                test_cases: vec![
+                    test_case(3, vec![]),
+                    test_case(
+                        10,
+                        vec![
+                            (
+                                "Expected 3, got 4 in calculator.md:12:9",
+                                Snooze::not_snooze(),
+                            ),
+                            (
+                                "Expected <b>, got a & c in calculator.md:13:9",
+                                Snooze::not_snooze(),
+                            ),
+                        ],
+                    ),
+                    test_case(20, vec![("Missing row in calculator.md:22:1", snoozed)]),
+                    TestCaseResult {
+                        skipped: Some("The test was stopped: Database is down".into()),
+                        ..test_case(30, vec![])
+                    },
+                ],
+                time: Duration::from_millis(1500),
+            },
+            TestSuiteResult {
+                name: "empty.md".into(),
+                test_cases: vec![],
+                time: Duration::ZERO,
+            },
+        ];
+        assert_eq!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="4" failures="1" skipped="2">
+  <testsuite name="calculator.md" tests="4" failures="1" skipped="2" time="1.500">
+    <testcase name="Calculator (calculator.md:3:1)" classname="calculator.md"/>
+    <testcase name="Calculator (calculator.md:10:1)" classname="calculator.md">
+      <failure message="Expected 3, got 4 in calculator.md:12:9">Expected 3, got 4 in calculator.md:12:9
+Expected &lt;b&gt;, got a &amp; c in calculator.md:13:9</failure>
+    </testcase>
+    <testcase name="Calculator (calculator.md:20:1)" classname="calculator.md">
+      <skipped message="snoozed until 2099-12-31">Missing row in calculator.md:22:1</skipped>
+    </testcase>
+    <testcase name="Calculator (calculator.md:30:1)" classname="calculator.md">
+      <skipped message="The test was stopped: Database is down"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="empty.md" tests="0" failures="0" skipped="0" time="0.000">
+  </testsuite>
+</testsuites>
+"#,
+            junit(&test_suites)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use self::{html::html, junit::junit};
+use crate::processor::TestCaseResult;
+use anyhow::{anyhow, bail, Error, Result};
+use std::fs::write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod html;
+mod junit;
+
+/// A report written once all the files have been tested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Report {
+    JUnit(PathBuf),
+    /// A directory with an annotated page for each file and an index.
+    Html(PathBuf),
+}
+
+impl FromStr for Report {
+    type Err = Error;
+
+    fn from_str(report: &str) -> Result<Self> {
+        let (format, path) = report
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected a report like `junit=path.xml`, got `{report}`"))?;
+        match format {
+            "junit" => Ok(Report::JUnit(PathBuf::from(path))),
+            "html" => Ok(Report::Html(PathBuf::from(path))),
+            _ => bail!("Unknown report format `{format}`"),
+        }
+    }
+}
+
+impl Report {
+    pub fn write(&self, test_suites: &[TestSuiteResult]) -> Result<()> {
+        match self {
+            Report::JUnit(path) => Ok(write(path, junit(test_suites)?)?),
+            Report::Html(dir) => html(dir, test_suites),
+        }
+    }
+}
+
+/// The results of a markdown file, or of a suite set up or tear down page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestSuiteResult {
+    pub name: String,
+    pub test_cases: Vec<TestCaseResult>,
+    pub time: Duration,
+}
+
+impl TestSuiteResult {
+    pub fn failures(&self) -> usize {
+        self.test_cases
+            .iter()
+            .filter(|test_case| test_case.failed())
+            .count()
+    }
+
+    /// Test cases whose failures are all snoozed.
+    pub fn snoozed(&self) -> usize {
+        self.test_cases
+            .iter()
+            .filter(|test_case| {
+                test_case.skipped.is_none()
+                    && !test_case.failed()
+                    && !test_case.failures().is_empty()
+            })
+            .count()
+    }
+
+    /// Test cases not executed, like the ones after a fixture stops the test.
+    pub fn skipped(&self) -> usize {
+        self.test_cases
+            .iter()
+            .filter(|test_case| test_case.skipped.is_some())
+            .count()
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_report() -> Result<()> {
+        assert_eq!(
+            Report::JUnit(PathBuf::from("target/report.xml")),
+            "junit=target/report.xml".parse()?
+        );
+        assert_eq!(
+            Report::Html(PathBuf::from("target/report")),
+            "html=target/report".parse()?
+        );
+        assert_eq!(
+            "Unknown report format `xml`",
+            "xml=report.xml".parse::<Report>().unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected a report like `junit=path.xml`, got `junit`",
+            "junit".parse::<Report>().unwrap_err().to_string()
+        );
+        Ok(())
+    }
+}
@@ -1,9 +1,18 @@
 use crate::port::CyclePort;
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use rand::Rng;
+use std::collections::HashSet;
 use std::io::{BufRead, BufReader, Read, Write};
-use std::net::TcpStream;
+use std::net::{Shutdown, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(unix)]
+use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
@@ -22,29 +31,95 @@ pub fn build_slim_server_connector(
         let mut rng = rand::thread_rng();
         Box::new(TcpSlimServerConnector {
             command,
-            port: CyclePort::new(
+            port: Mutex::new(CyclePort::new(
                 rng.gen_range(port..(port + (pool_size - 1))),
                 port,
                 pool_size,
-            ),
+            )),
+            ports_in_use: Arc::default(),
             pipe_output,
         })
     }
 }
 
-pub trait SlimServerConnector {
-    fn start_and_connect(&mut self) -> Result<Box<dyn SlimServer>>;
+/// Shared by the jobs testing files concurrently, each of them starting its own server.
+pub trait SlimServerConnector: Send + Sync {
+    fn start_and_connect(&self) -> Result<Box<dyn SlimServer>>;
+
+    /// If the server accepts a single connection, that every file and suite page has to share.
+    fn single_connection(&self) -> bool {
+        false
+    }
 }
 
-pub trait SlimServer {
-    fn reader(&mut self) -> Result<Box<dyn Read>>;
-    fn writer(&mut self) -> Result<Box<dyn Write>>;
+pub trait SlimServer: Send {
+    fn reader(&mut self) -> Result<Box<dyn Read + Send>>;
+    fn writer(&mut self) -> Result<Box<dyn Write + Send>>;
+    /// Stops the server from another thread, unblocking any read from it.
+    fn kill_switch(&mut self) -> Result<KillSwitch>;
     fn close(&mut self) -> Result<()>;
 }
 
+pub struct KillSwitch(Box<dyn Fn() -> Result<()> + Send + Sync>);
+
+impl KillSwitch {
+    pub fn new(kill: impl Fn() -> Result<()> + Send + Sync + 'static) -> Self {
+        Self(Box::new(kill))
+    }
+
+    pub fn kill(&self) -> Result<()> {
+        (self.0)()
+    }
+}
+
+/// Kills the process started by `sh -c` and everything it started, like the server under `cargo run`.
+fn process_group_kill_switch(child: &Child) -> KillSwitch {
+    let pid = child.id();
+    KillSwitch::new(move || kill_process_group(pid))
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) -> Result<()> {
+    Command::new("kill")
+        .args(["-s", "KILL", "--", &format!("-{pid}")])
+        .stderr(Stdio::null())
+        .status()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(pid: u32) -> Result<()> {
+    Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    Ok(())
+}
+
+/// Waits for the server to exit after the connection is closed, killing it if it takes longer than
+/// 10 seconds.
+fn wait_or_kill(child: &mut Child) -> Result<()> {
+    let start = Instant::now();
+    let time_limit = Duration::from_secs(10);
+    let sleep_time = Duration::from_millis(50);
+    while child.try_wait()?.is_none() {
+        if start.elapsed() > time_limit {
+            kill_process_group(child.id())?;
+            child.wait()?;
+            bail!("The slim server did not exit after closing the connection, so it was killed");
+        }
+        sleep(sleep_time);
+    }
+    Ok(())
+}
+
 pub struct TcpSlimServerConnector {
     command: String,
-    port: CyclePort,
+    port: Mutex<CyclePort>,
+    /// Ports of the servers still running, skipped when cycling through the pool so two jobs never
+    /// share one.
+    ports_in_use: Arc<Mutex<HashSet<u16>>>,
     pipe_output: bool,
 }
 
@@ -55,42 +130,65 @@ fn spawn_server(
     stderr: Stdio,
     stdin: Stdio,
 ) -> Result<Child> {
-    let child = Command::new("sh")
+    let mut command_builder = Command::new("sh");
+    command_builder
         .arg("-c")
         .arg(command.replace("%p", &port.to_string()))
         .stdout(stdout)
         .stderr(stderr)
-        .stdin(stdin)
-        .spawn()?;
-    Ok(child)
+        .stdin(stdin);
+    // In its own process group, so it can be killed along with the processes it starts.
+    #[cfg(unix)]
+    command_builder.process_group(0);
+    Ok(command_builder.spawn()?)
 }
 
 impl SlimServerConnector for TcpSlimServerConnector {
-    fn start_and_connect(&mut self) -> Result<Box<dyn SlimServer>> {
+    fn start_and_connect(&self) -> Result<Box<dyn SlimServer>> {
         let stdout = build_stdio(self.pipe_output);
         let stderr = build_stdio(self.pipe_output);
-        self.port.new_port();
-        let child = spawn_server(
-            &self.command,
-            self.port.to_port(),
-            stdout,
-            stderr,
-            Stdio::null(),
-        )?;
-        let start = Instant::now();
-        let time_limit = Duration::from_secs(10);
-        let sleep_time = Duration::from_millis(100);
-        let stream = loop {
-            if let Ok(tcp_stream) = TcpStream::connect(format!("127.0.0.1:{}", self.port.to_port()))
-            {
-                break tcp_stream;
-            }
-            if start.elapsed() > time_limit {
-                bail!("Failed to connect to slim server");
-            }
-            sleep(sleep_time);
+        let port = {
+            let mut port = self
+                .port
+                .lock()
+                .map_err(|_| anyhow!("Failed to get a port for the slim server"))?;
+            let mut ports_in_use = self
+                .ports_in_use
+                .lock()
+                .map_err(|_| anyhow!("Failed to get a port for the slim server"))?;
+            let Some(port) = port.new_port_not_in(&ports_in_use) else {
+                bail!("Every port of the pool is used by a running slim server. Increase the pool size or run fewer jobs");
+            };
+            ports_in_use.insert(port);
+            port
+        };
+        let port_in_use = PortInUse {
+            port,
+            ports_in_use: self.ports_in_use.clone(),
         };
-        Ok(Box::new(TcpSlimServer { child, stream }))
+        let child = spawn_server(&self.command, port, stdout, stderr, Stdio::null())?;
+        let stream = connect_with_retry(|| TcpStream::connect(format!("127.0.0.1:{port}")))?;
+        Ok(Box::new(TcpSlimServer {
+            child,
+            stream,
+            _port: port_in_use,
+        }))
+    }
+}
+
+/// Keeps trying to connect while the slim server starts, for up to 10 seconds.
+fn connect_with_retry<S>(connect: impl Fn() -> std::io::Result<S>) -> Result<S> {
+    let start = Instant::now();
+    let time_limit = Duration::from_secs(10);
+    let sleep_time = Duration::from_millis(100);
+    loop {
+        if let Ok(stream) = connect() {
+            return Ok(stream);
+        }
+        if start.elapsed() > time_limit {
+            bail!("Failed to connect to slim server");
+        }
+        sleep(sleep_time);
     }
 }
 
@@ -105,20 +203,38 @@ fn build_stdio(pipe_output: bool) -> Stdio {
 struct TcpSlimServer {
     child: Child,
     stream: TcpStream,
+    _port: PortInUse,
+}
+
+/// Gives the port back to the pool once its server is dropped.
+struct PortInUse {
+    port: u16,
+    ports_in_use: Arc<Mutex<HashSet<u16>>>,
+}
+
+impl Drop for PortInUse {
+    fn drop(&mut self) {
+        if let Ok(mut ports_in_use) = self.ports_in_use.lock() {
+            ports_in_use.remove(&self.port);
+        }
+    }
 }
 
 impl SlimServer for TcpSlimServer {
-    fn reader(&mut self) -> Result<Box<dyn Read>> {
+    fn reader(&mut self) -> Result<Box<dyn Read + Send>> {
         Ok(Box::new(self.stream.try_clone()?))
     }
 
-    fn writer(&mut self) -> Result<Box<dyn Write>> {
+    fn writer(&mut self) -> Result<Box<dyn Write + Send>> {
         Ok(Box::new(self.stream.try_clone()?))
     }
 
+    fn kill_switch(&mut self) -> Result<KillSwitch> {
+        Ok(process_group_kill_switch(&self.child))
+    }
+
     fn close(&mut self) -> Result<()> {
-        self.child.wait()?;
-        Ok(())
+        wait_or_kill(&mut self.child)
     }
 }
 
@@ -133,7 +249,7 @@ struct StdoutSlimServer {
 }
 
 impl SlimServerConnector for StdoutSlimServerConnector {
-    fn start_and_connect(&mut self) -> Result<Box<dyn SlimServer>> {
+    fn start_and_connect(&self) -> Result<Box<dyn SlimServer>> {
         let child = spawn_server(
             &self.command,
             1,
@@ -150,7 +266,7 @@ impl SlimServerConnector for StdoutSlimServerConnector {
 }
 
 impl SlimServer for StdoutSlimServer {
-    fn reader(&mut self) -> Result<Box<dyn Read>> {
+    fn reader(&mut self) -> Result<Box<dyn Read + Send>> {
         Ok(Box::new(
             self.child
                 .stdout
@@ -159,7 +275,7 @@ impl SlimServer for StdoutSlimServer {
         ))
     }
 
-    fn writer(&mut self) -> Result<Box<dyn Write>> {
+    fn writer(&mut self) -> Result<Box<dyn Write + Send>> {
         Ok(Box::new(
             self.child
                 .stdin
@@ -168,8 +284,12 @@ impl SlimServer for StdoutSlimServer {
         ))
     }
 
+    fn kill_switch(&mut self) -> Result<KillSwitch> {
+        Ok(process_group_kill_switch(&self.child))
+    }
+
     fn close(&mut self) -> Result<()> {
-        self.child.wait()?;
+        wait_or_kill(&mut self.child)?;
         if self.pipe_output {
             let mut child_stderr = self
                 .child
@@ -194,3 +314,139 @@ impl SlimServer for StdoutSlimServer {
         Ok(())
     }
 }
+
+/// Address of a slim server that is already running, like one under a debugger or in another
+/// container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerAddress {
+    /// A `host:port` to connect through TCP.
+    Tcp(String),
+    /// A path to a Unix domain socket, written as `unix:path`.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for ServerAddress {
+    type Err = Error;
+
+    fn from_str(address: &str) -> Result<Self> {
+        #[cfg(unix)]
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(Self::Tcp(address.to_string()))
+            }
+            _ => bail!("Expected an address like `host:port` or `unix:path`, got `{address}`"),
+        }
+    }
+}
+
+/// Connects to a slim server that is already running, without starting anything.
+pub struct ExistingSlimServerConnector {
+    address: ServerAddress,
+}
+
+impl ExistingSlimServerConnector {
+    pub fn new(address: ServerAddress) -> Self {
+        Self { address }
+    }
+}
+
+impl SlimServerConnector for ExistingSlimServerConnector {
+    fn start_and_connect(&self) -> Result<Box<dyn SlimServer>> {
+        Ok(match &self.address {
+            ServerAddress::Tcp(address) => Box::new(ExistingSlimServer {
+                stream: connect_with_retry(|| TcpStream::connect(address))?,
+            }),
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => Box::new(ExistingSlimServer {
+                stream: connect_with_retry(|| UnixStream::connect(path))?,
+            }),
+        })
+    }
+
+    /// A running server stops once its connection says goodbye.
+    fn single_connection(&self) -> bool {
+        true
+    }
+}
+
+/// A stream that can be read and written at the same time through its clones.
+trait CloneableStream: Read + Write + Send + Sync + Sized + 'static {
+    fn try_clone_stream(&self) -> std::io::Result<Self>;
+    fn shutdown_stream(&self) -> std::io::Result<()>;
+}
+
+impl CloneableStream for TcpStream {
+    fn try_clone_stream(&self) -> std::io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn shutdown_stream(&self) -> std::io::Result<()> {
+        self.shutdown(Shutdown::Both)
+    }
+}
+
+#[cfg(unix)]
+impl CloneableStream for UnixStream {
+    fn try_clone_stream(&self) -> std::io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn shutdown_stream(&self) -> std::io::Result<()> {
+        self.shutdown(Shutdown::Both)
+    }
+}
+
+struct ExistingSlimServer<S> {
+    stream: S,
+}
+
+impl<S: CloneableStream> SlimServer for ExistingSlimServer<S> {
+    fn reader(&mut self) -> Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.stream.try_clone_stream()?))
+    }
+
+    fn writer(&mut self) -> Result<Box<dyn Write + Send>> {
+        Ok(Box::new(self.stream.try_clone_stream()?))
+    }
+
+    /// The server was not started by us, so only the connection to it is shut down.
+    fn kill_switch(&mut self) -> Result<KillSwitch> {
+        let stream = self.stream.try_clone_stream()?;
+        Ok(KillSwitch::new(move || Ok(stream.shutdown_stream()?)))
+    }
+
+    /// The server was not started by us, so it is left running.
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_server_address() -> Result<()> {
+        assert_eq!(
+            ServerAddress::Tcp("localhost:8085".into()),
+            "localhost:8085".parse()?
+        );
+        assert_eq!(
+            ServerAddress::Tcp("[::1]:8085".into()),
+            "[::1]:8085".parse()?
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            ServerAddress::Unix("/tmp/slim.sock".into()),
+            "unix:/tmp/slim.sock".parse()?
+        );
+        assert!("localhost".parse::<ServerAddress>().is_err());
+        assert!(":8085".parse::<ServerAddress>().is_err());
+        assert!("localhost:http".parse::<ServerAddress>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! A rust_slim server running on a thread of the tests, with a few fixtures to test the app
+//! against.
+use crate::slim_server_connector::{KillSwitch, SlimServer, SlimServerConnector};
+use anyhow::{anyhow, Result};
+use rust_slim::fixture;
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{spawn, JoinHandle};
+
+thread_local! {
+    /// Disconnected once the server running on this thread is killed.
+    static KILLED: RefCell<Option<Receiver<()>>> = const { RefCell::new(None) };
+}
+
+#[derive(Default)]
+struct Calculator {
+    a: i64,
+    b: i64,
+}
+
+#[fixture("Fixtures.Calculator")]
+impl Calculator {
+    pub fn set_a(&mut self, a: i64) {
+        self.a = a;
+    }
+
+    pub fn set_b(&mut self, b: i64) {
+        self.b = b;
+    }
+
+    pub fn sum(&self) -> i64 {
+        self.a + self.b
+    }
+
+    /// Stands for a slow fixture, only returning once the server is killed, or right away when
+    /// nothing can kill it.
+    pub fn block_until_killed(&self) {
+        KILLED.with_borrow(|killed| {
+            if let Some(killed) = killed {
+                let _ = killed.recv();
+            }
+        });
+    }
+}
+
+/// Made at the start of each file when it is the reset fixture.
+#[derive(Default)]
+struct Reset;
+
+#[fixture("Fixtures.Reset")]
+impl Reset {}
+
+/// Starts a server on a thread for each connection, counting them and recording everything sent to
+/// them. Its clones share those, so a test can keep one while the app owns another.
+#[derive(Default, Clone)]
+pub struct TestSlimServerConnector {
+    started: Arc<AtomicUsize>,
+    sent: Arc<Mutex<Vec<u8>>>,
+}
+
+impl TestSlimServerConnector {
+    pub fn started(&self) -> usize {
+        self.started.load(Ordering::SeqCst)
+    }
+
+    /// Everything sent to the servers, in the slim protocol.
+    pub fn sent(&self) -> String {
+        let sent = self.sent.lock().expect("Nothing panics while recording");
+        String::from_utf8_lossy(&sent).to_string()
+    }
+}
+
+impl SlimServerConnector for TestSlimServerConnector {
+    fn start_and_connect(&self) -> Result<Box<dyn SlimServer>> {
+        let (killed, released) = channel();
+        let (address, thread) = self.spawn(Some(released))?;
+        let stream = TcpStream::connect(address)?;
+        Ok(Box::new(TestSlimServer {
+            stream,
+            thread: Some(thread),
+            killed: Some(killed),
+        }))
+    }
+}
+
+impl TestSlimServerConnector {
+    /// Starts a server on a thread that accepts a single connection, like one started by hand,
+    /// returning its address and the thread that ends once the connection is closed.
+    pub fn serve(&self) -> Result<(SocketAddr, JoinHandle<Result<()>>)> {
+        self.spawn(None)
+    }
+
+    fn spawn(&self, killed: Option<Receiver<()>>) -> Result<(SocketAddr, JoinHandle<Result<()>>)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let sent = self.sent.clone();
+        let thread = spawn(move || -> Result<()> {
+            KILLED.set(killed);
+            let (stream, _) = listener.accept()?;
+            drop(listener);
+            let reader = Recorder {
+                reader: stream.try_clone()?,
+                sent,
+            };
+            let mut server = rust_slim::SlimServer::new(reader, stream);
+            server.add_fixture::<Calculator>();
+            server.add_fixture::<Reset>();
+            // A killed server fails to read the next instruction.
+            let _ = server.run();
+            Ok(())
+        });
+        self.started.fetch_add(1, Ordering::SeqCst);
+        Ok((address, thread))
+    }
+}
+
+struct Recorder {
+    reader: TcpStream,
+    sent: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Read for Recorder {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.sent
+            .lock()
+            .map_err(|_| std::io::Error::other("Failed to record"))?
+            .extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+struct TestSlimServer {
+    stream: TcpStream,
+    thread: Option<JoinHandle<Result<()>>>,
+    killed: Option<Sender<()>>,
+}
+
+impl SlimServer for TestSlimServer {
+    fn reader(&mut self) -> Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.stream.try_clone()?))
+    }
+
+    fn writer(&mut self) -> Result<Box<dyn Write + Send>> {
+        Ok(Box::new(self.stream.try_clone()?))
+    }
+
+    fn kill_switch(&mut self) -> Result<KillSwitch> {
+        let stream = self.stream.try_clone()?;
+        let killed = Mutex::new(self.killed.take());
+        Ok(KillSwitch::new(move || {
+            // Releases a fixture blocked until then, so the server can finish.
+            killed.lock().map_err(|_| anyhow!("Failed to kill"))?.take();
+            Ok(stream.shutdown(Shutdown::Both)?)
+        }))
+    }
+
+    /// Waits for the server to finish, once the connection is closed or killed.
+    fn close(&mut self) -> Result<()> {
+        self.thread
+            .take()
+            .map(|thread| {
+                thread
+                    .join()
+                    .map_err(|_| anyhow!("The slim server panicked"))?
+            })
+            .transpose()?;
+        Ok(())
+    }
+}
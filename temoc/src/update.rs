@@ -0,0 +1,303 @@
+use crate::processor::{ExpectedResultValue, TestCaseResult};
+use anyhow::{anyhow, Result};
+use markdown::mdast::{AlignKind, Node, Table};
+use markdown::{to_mdast, ParseOptions};
+use slim_protocol::InstructionResultValue;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+/// Which cells get the actual value written into the markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Update {
+    /// Every cell whose expected value is different from the actual one.
+    All,
+    /// Only the cells left blank, which show the actual value instead of checking it.
+    OnlyEmpty,
+}
+
+/// Replaces the value of a cell, as long as it still has the value that was expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edit {
+    line: usize,
+    column: usize,
+    expected: String,
+    actual: String,
+}
+
+/// Writes the actual values of the failing cells of the file back into it, realigning the tables
+/// that changed. Returns the number of updated cells.
+pub fn update_markdown(file: &Path, results: &[TestCaseResult], update: Update) -> Result<usize> {
+    let edits = edits(&file.display().to_string(), results, update);
+    if edits.is_empty() {
+        return Ok(0);
+    }
+    let source = read_to_string(file)?;
+    let (updated, updated_cells) = apply_edits(&source, &edits)?;
+    if updated_cells > 0 {
+        write(file, updated)?;
+    }
+    Ok(updated_cells)
+}
+
+fn edits(file: &str, results: &[TestCaseResult], update: Update) -> Vec<Edit> {
+    results
+        .iter()
+        .filter(|test_case| test_case.file == file)
+        .flat_map(|test_case| &test_case.results)
+        .filter_map(|result| {
+            let position = &result.expected.position;
+            // Cells of included files or of scenarios are shared, so they are never updated.
+            if position.is_included() || position.origin() != position {
+                return None;
+            }
+            let InstructionResultValue::String(actual) = &result.actual else {
+                return None;
+            };
+            let expected = match &result.expected.value {
+                ExpectedResultValue::Blank => String::new(),
+                ExpectedResultValue::String(expected)
+                    if update == Update::All && !result.passed() =>
+                {
+                    expected.clone()
+                }
+                _ => return None,
+            };
+            Some(Edit {
+                line: position.line(),
+                column: position.column(),
+                expected,
+                actual: actual.clone(),
+            })
+        })
+        .collect()
+}
+
+fn apply_edits(source: &str, edits: &[Edit]) -> Result<(String, usize)> {
+    let markdown = to_mdast(source, &ParseOptions::gfm())
+        .map_err(|err| anyhow!("Error parsing markdown {err}"))?;
+    let mut updated = source.to_string();
+    let mut updated_cells = 0;
+    // From the last table to the first, so the offsets of the ones not yet updated stay valid.
+    for node in markdown.children().into_iter().flatten().rev() {
+        let Node::Table(table) = node else {
+            continue;
+        };
+        let Some(position) = &table.position else {
+            continue;
+        };
+        let (mut rows, applied) = table_rows(table, source, edits);
+        if applied == 0 {
+            continue;
+        }
+        for row in rows.iter_mut() {
+            row.resize(table.align.len().max(row.len()), String::new());
+        }
+        updated.replace_range(
+            position.start.offset..position.end.offset,
+            &render_table(&rows, &table.align),
+        );
+        updated_cells += applied;
+    }
+    Ok((updated, updated_cells))
+}
+
+/// The text of each cell of the table, with the edits that belong to it applied.
+fn table_rows(table: &Table, source: &str, edits: &[Edit]) -> (Vec<Vec<String>>, usize) {
+    let mut applied = 0;
+    let rows = table
+        .children
+        .iter()
+        .map(|row| {
+            row.children()
+                .into_iter()
+                .flatten()
+                .filter_map(|cell| cell.position())
+                .map(|position| {
+                    let text = cell_text(&source[position.start.offset..position.end.offset]);
+                    let edit = edits.iter().find(|edit| {
+                        edit.line == position.start.line
+                            && edit.column >= position.start.column
+                            && edit.column < position.end.column
+                            && edit.expected == text
+                    });
+                    match edit {
+                        Some(edit) => {
+                            applied += 1;
+                            edit.actual.replace('|', "\\|").replace('\n', " ")
+                        }
+                        None => text,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    (rows, applied)
+}
+
+/// Removes the pipes delimiting the cell and the whitespace around its content.
+fn cell_text(cell: &str) -> String {
+    let cell = cell.trim();
+    let cell = cell.strip_prefix('|').unwrap_or(cell);
+    let cell = match cell.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => cell,
+    };
+    cell.trim().to_string()
+}
+
+fn render_table(rows: &[Vec<String>], align: &[AlignKind]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or_default()
+                .max(3)
+        })
+        .collect();
+    let render_row = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        lines.push(render_row(
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect(),
+        ));
+        if index == 0 {
+            lines.push(render_row(
+                widths
+                    .iter()
+                    .enumerate()
+                    .map(
+                        |(column, width)| match align.get(column).unwrap_or(&AlignKind::None) {
+                            AlignKind::Left => format!(":{}", "-".repeat(width - 1)),
+                            AlignKind::Right => format!("{}:", "-".repeat(width - 1)),
+                            AlignKind::Center => format!(":{}:", "-".repeat(width - 2)),
+                            AlignKind::None => "-".repeat(*width),
+                        },
+                    )
+                    .collect(),
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::{CheckedResult, ExpectedResult, Failure, Position, Snooze};
+    use slim_protocol::Id;
+
+    fn edit(line: usize, column: usize, expected: &str, actual: &str) -> Edit {
+        Edit {
+            line,
+            column,
+            expected: expected.into(),
+            actual: actual.into(),
+        }
+    }
+
+    fn checked(line: usize, expected: ExpectedResultValue, actual: &str) -> CheckedResult {
+        let mut checked = CheckedResult {
+            expected: ExpectedResult::ok(Id::new(), Position::new(line, 9)),
+            actual: InstructionResultValue::String(actual.into()),
+            snooze: Snooze::not_snooze(),
+            failures: vec![],
+        };
+        checked.expected.value = expected;
+        if checked.expected.value != checked.actual {
+            checked.failures.push(Failure {
+                position: Position::new(line, 9),
+                message: "Failed".into(),
+            });
+        }
+        checked
+    }
+
+    #[test]
+    fn edit_blank_cells_and_failing_values() {
+        let results = vec![TestCaseResult {
+            name: "Calculator".into(),
+            file: "calculator.md".into(),
+            position: Position::new(1, 1),
+            results: vec![
+                checked(5, ExpectedResultValue::Blank, "15"),
+                checked(6, ExpectedResultValue::String("3".into()), "4"),
+                checked(7, ExpectedResultValue::String("5".into()), "5"),
+                checked(8, ExpectedResultValue::String("".into()), "6"),
+            ],
+            skipped: None,
+        }];
+        assert_eq!(
+            vec![edit(5, 9, "", "15")],
+            edits("calculator.md", &results, Update::OnlyEmpty)
+        );
+        assert_eq!(
+            vec![
+                edit(5, 9, "", "15"),
+                edit(6, 9, "3", "4"),
+                edit(8, 9, "", "6")
+            ],
+            edits("calculator.md", &results, Update::All)
+        );
+        assert!(edits("division.md", &results, Update::All).is_empty());
+    }
+
+    #[test]
+    fn update_cells_and_realign_table() -> Result<()> {
+        let source = "# Calculator
+
+| a | b | sum? |
+|---|--:|:----:|
+| 1 | 2 | 4 |
+| 10 | 5 |  |
+
+| a | sum? |
+|---|---|
+| 1 | 3 |
+";
+        let (updated, updated_cells) = apply_edits(
+            source,
+            &[
+                edit(5, 11, "4", "3"),
+                edit(6, 11, "", "15"),
+                edit(10, 7, "5", "1"),
+                edit(10, 3, "2", "1"),
+            ],
+        )?;
+        assert_eq!(2, updated_cells);
+        assert_eq!(
+            "# Calculator
+
+| a   | b   | sum? |
+| --- | --: | :--: |
+| 1   | 2   | 3    |
+| 10  | 5   | 15   |
+
+| a | sum? |
+|---|---|
+| 1 | 3 |
+",
+            updated
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn escape_pipes_of_actual_values() -> Result<()> {
+        let (updated, updated_cells) = apply_edits(
+            "| a | show? |\n| --- | --- |\n| 1 | |\n",
+            &[edit(3, 7, "", "a|b")],
+        )?;
+        assert_eq!(1, updated_cells);
+        assert_eq!(
+            "| a   | show? |\n| --- | ----- |\n| 1   | a\\|b  |\n",
+            updated
+        );
+        Ok(())
+    }
+}
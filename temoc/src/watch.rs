@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fs::{metadata, read_dir};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Watches the test files and the sources of the fixtures, gathering what changed until taken.
+pub struct Watch {
+    tests: Watcher,
+    sources: Watcher,
+    changes: Changes,
+}
+
+/// The files that changed, in the tests and in the sources of the fixtures.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Changes {
+    pub tests: Vec<PathBuf>,
+    pub sources: Vec<PathBuf>,
+}
+
+impl Watch {
+    /// Test directories are only watched recursively when they are tested recursively, while the
+    /// sources always are.
+    pub fn new(tests: Vec<PathBuf>, recursive: bool, sources: Vec<PathBuf>) -> Self {
+        Self {
+            tests: Watcher::new(tests, recursive),
+            sources: Watcher::new(sources, true),
+            changes: Changes::default(),
+        }
+    }
+
+    /// Checks the paths again, returning if anything changed since the last time.
+    pub fn poll(&mut self) -> bool {
+        let tests = self.tests.changes();
+        let sources = self.sources.changes();
+        let changed = !tests.is_empty() || !sources.is_empty();
+        merge(&mut self.changes.tests, tests);
+        merge(&mut self.changes.sources, sources);
+        changed
+    }
+
+    /// The changes gathered since they were last taken.
+    pub fn take(&mut self) -> Changes {
+        std::mem::take(&mut self.changes)
+    }
+}
+
+fn merge(files: &mut Vec<PathBuf>, changed: Vec<PathBuf>) {
+    for file in changed {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+}
+
+/// Finds the files that changed under some paths by comparing their modification times. It polls
+/// them, so it works the same on every platform and file system.
+struct Watcher {
+    roots: Vec<PathBuf>,
+    recursive: bool,
+    modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl Watcher {
+    fn new(roots: Vec<PathBuf>, recursive: bool) -> Self {
+        let modified = scan(&roots, recursive);
+        Self {
+            roots,
+            recursive,
+            modified,
+        }
+    }
+
+    /// The files created, modified or removed since the last time it was called.
+    fn changes(&mut self) -> Vec<PathBuf> {
+        let modified = scan(&self.roots, self.recursive);
+        let mut changes: Vec<PathBuf> = modified
+            .iter()
+            .filter(|(file, time)| self.modified.get(*file) != Some(time))
+            .map(|(file, _)| file.clone())
+            .chain(
+                self.modified
+                    .keys()
+                    .filter(|file| !modified.contains_key(*file))
+                    .cloned(),
+            )
+            .collect();
+        changes.sort();
+        self.modified = modified;
+        changes
+    }
+}
+
+/// Files that disappear while scanning are left out, they are reported as removed by the next scan.
+fn scan(roots: &[PathBuf], recursive: bool) -> HashMap<PathBuf, SystemTime> {
+    let mut modified = HashMap::new();
+    for root in roots {
+        if root.is_dir() {
+            scan_dir(root, recursive, &mut modified);
+        } else if let Ok(time) = metadata(root).and_then(|metadata| metadata.modified()) {
+            modified.insert(root.clone(), time);
+        }
+    }
+    modified
+}
+
+/// Hidden entries and `target` directories are skipped, as editors and builds keep changing them.
+fn scan_dir(dir: &Path, recursive: bool, modified: &mut HashMap<PathBuf, SystemTime>) {
+    let Ok(entries) = read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if recursive && file_name != "target" {
+                scan_dir(&path, recursive, modified);
+            }
+        } else if let Ok(time) = metadata.modified() {
+            modified.insert(path, time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, remove_file, write, File};
+    use std::time::Duration;
+    use ulid::Ulid;
+
+    fn touch(file: &Path) {
+        File::options()
+            .write(true)
+            .open(file)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+    }
+
+    #[test]
+    fn find_the_changed_files() {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("nested")).unwrap();
+        create_dir_all(dir.join("target")).unwrap();
+        write(dir.join("calculator.md"), "").unwrap();
+        write(dir.join("nested/division.md"), "").unwrap();
+        let mut watcher = Watcher::new(vec![dir.clone()], true);
+        assert!(watcher.changes().is_empty());
+
+        touch(&dir.join("nested/division.md"));
+        write(dir.join("multiplication.md"), "").unwrap();
+        write(dir.join(".calculator.md.swp"), "").unwrap();
+        write(dir.join("target/fixture"), "").unwrap();
+        assert_eq!(
+            vec![
+                dir.join("multiplication.md"),
+                dir.join("nested/division.md")
+            ],
+            watcher.changes()
+        );
+        assert!(watcher.changes().is_empty());
+
+        remove_file(dir.join("calculator.md")).unwrap();
+        assert_eq!(vec![dir.join("calculator.md")], watcher.changes());
+
+        let mut not_recursive = Watcher::new(vec![dir.clone()], false);
+        touch(&dir.join("nested/division.md"));
+        assert!(not_recursive.changes().is_empty());
+        remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gather_the_changes_until_taken() {
+        let dir = temp_dir().join(Ulid::new().to_string());
+        create_dir_all(dir.join("tests")).unwrap();
+        create_dir_all(dir.join("src")).unwrap();
+        write(dir.join("tests/calculator.md"), "").unwrap();
+        write(dir.join("src/lib.rs"), "").unwrap();
+        let mut watch = Watch::new(vec![dir.join("tests")], false, vec![dir.join("src")]);
+        assert!(!watch.poll());
+
+        touch(&dir.join("tests/calculator.md"));
+        assert!(watch.poll());
+        touch(&dir.join("src/lib.rs"));
+        touch(&dir.join("tests/calculator.md"));
+        assert!(watch.poll());
+        assert!(!watch.poll());
+        assert_eq!(
+            Changes {
+                tests: vec![dir.join("tests/calculator.md")],
+                sources: vec![dir.join("src/lib.rs")],
+            },
+            watch.take()
+        );
+        assert_eq!(Changes::default(), watch.take());
+        remove_dir_all(dir).unwrap();
+    }
+}
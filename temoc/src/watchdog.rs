@@ -0,0 +1,236 @@
+use crate::slim_server_connector::KillSwitch;
+use anyhow::{bail, Error, Result};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::scope;
+use std::time::{Duration, Instant};
+
+/// How long the slim server can take before it is killed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// For the results of each batch of instructions sent to it.
+    pub batch: Option<Duration>,
+    /// For all the batches of a file, including its set up and tear down.
+    pub file: Option<Duration>,
+}
+
+/// The error of a batch of instructions that didn't finish in time, with the timeout it exceeded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timeout {
+    Batch(Duration),
+    File(Duration),
+}
+
+impl Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timeout::Batch(timeout) => write!(
+                f,
+                "Timed out after {}s waiting for the results of the slim server",
+                timeout.as_secs_f64()
+            ),
+            Timeout::File(timeout) => write!(
+                f,
+                "Timed out after {}s testing the file",
+                timeout.as_secs_f64()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+/// The error of a batch of instructions that was stopped because the tests are no longer needed,
+/// like when a new change arrives in watch mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The tests were cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// How often a batch checks if it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// Kills the slim server of a file when a batch of instructions, or the whole file, takes longer
+/// than the timeouts, or when the tests are cancelled.
+pub struct Watchdog<'a> {
+    timeouts: Timeouts,
+    file_deadline: Option<Instant>,
+    kill_switch: &'a KillSwitch,
+    cancelled: Option<&'a Arc<AtomicBool>>,
+    killed: AtomicBool,
+}
+
+impl<'a> Watchdog<'a> {
+    pub fn new(
+        timeouts: Timeouts,
+        kill_switch: &'a KillSwitch,
+        cancelled: Option<&'a Arc<AtomicBool>>,
+    ) -> Self {
+        Self {
+            timeouts,
+            file_deadline: timeouts.file.map(|file| Instant::now() + file),
+            kill_switch,
+            cancelled,
+            killed: AtomicBool::new(false),
+        }
+    }
+
+    /// If the server was killed, so it can't be used anymore.
+    pub fn killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .is_some_and(|cancelled| cancelled.load(Ordering::SeqCst))
+    }
+
+    /// Runs a batch of instructions, failing with [Timeout] or [Cancelled] if the server had to be
+    /// killed.
+    pub fn run<T>(&self, batch: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.is_cancelled() {
+            return Err(Error::new(Cancelled));
+        }
+        if self.killed() {
+            bail!("The slim server was already killed after timing out");
+        }
+        let remaining = self
+            .file_deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let timeout = match (self.timeouts.batch, self.timeouts.file, remaining) {
+            (Some(batch), _, Some(remaining)) if batch < remaining => Some(Timeout::Batch(batch)),
+            (_, Some(file), Some(_)) => Some(Timeout::File(file)),
+            (Some(batch), _, _) => Some(Timeout::Batch(batch)),
+            _ => None,
+        };
+        if timeout.is_none() && self.cancelled.is_none() {
+            return batch();
+        }
+        let deadline = match timeout {
+            Some(Timeout::Batch(batch)) => Some(Instant::now() + batch),
+            Some(Timeout::File(_)) => self.file_deadline,
+            None => None,
+        };
+        let (sender, receiver) = channel::<()>();
+        let result = scope(|scope| {
+            scope.spawn(move || loop {
+                let wait = deadline
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+                    .unwrap_or(CANCEL_POLL);
+                let wait = match self.cancelled {
+                    Some(_) => wait.min(CANCEL_POLL),
+                    None => wait,
+                };
+                if receiver.recv_timeout(wait) != Err(RecvTimeoutError::Timeout) {
+                    break;
+                }
+                if self.is_cancelled()
+                    || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    self.killed.store(true, Ordering::SeqCst);
+                    // If it can't be killed there is nothing else to do, the read will keep waiting.
+                    let _ = self.kill_switch.kill();
+                    break;
+                }
+            });
+            let result = batch();
+            let _ = sender.send(());
+            result
+        });
+        if self.killed() {
+            return Err(match timeout.filter(|_| !self.is_cancelled()) {
+                Some(timeout) => Error::new(timeout),
+                None => Error::new(Cancelled),
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc::Sender;
+    use std::thread::sleep;
+
+    fn kill_switch(killed: Sender<()>) -> KillSwitch {
+        KillSwitch::new(move || Ok(killed.send(())?))
+    }
+
+    #[test]
+    fn kill_the_server_when_a_batch_times_out() {
+        let (sender, killed) = channel();
+        let kill_switch = kill_switch(sender);
+        let watchdog = Watchdog::new(
+            Timeouts {
+                batch: Some(Duration::from_millis(50)),
+                file: None,
+            },
+            &kill_switch,
+            None,
+        );
+        assert_eq!(1, watchdog.run(|| Ok(1)).unwrap());
+        assert!(!watchdog.killed());
+        // Like a read from the server, it only returns once the server is killed.
+        let error = watchdog.run(|| Ok(killed.recv()?)).unwrap_err();
+        assert_eq!(
+            Some(&Timeout::Batch(Duration::from_millis(50))),
+            error.downcast_ref::<Timeout>()
+        );
+        assert!(watchdog.killed());
+        assert!(watchdog.run(|| Ok(())).is_err());
+    }
+
+    #[test]
+    fn batches_share_the_file_timeout() {
+        let (sender, killed) = channel();
+        let kill_switch = kill_switch(sender);
+        let watchdog = Watchdog::new(
+            Timeouts {
+                batch: Some(Duration::from_secs(60)),
+                file: Some(Duration::from_secs(1)),
+            },
+            &kill_switch,
+            None,
+        );
+        watchdog
+            .run(|| {
+                sleep(Duration::from_millis(500));
+                Ok(())
+            })
+            .unwrap();
+        let started = Instant::now();
+        let error = watchdog.run(|| Ok(killed.recv()?)).unwrap_err();
+        assert_eq!(
+            Some(&Timeout::File(Duration::from_secs(1))),
+            error.downcast_ref::<Timeout>()
+        );
+        // Only what the first batch left of the file timeout.
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn kill_the_server_when_the_tests_are_cancelled() {
+        let (sender, killed) = channel();
+        let kill_switch = kill_switch(sender);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let watchdog = Watchdog::new(Timeouts::default(), &kill_switch, Some(&cancelled));
+        let error = watchdog
+            .run(|| {
+                cancelled.store(true, Ordering::SeqCst);
+                Ok(killed.recv()?)
+            })
+            .unwrap_err();
+        assert!(error.is::<Cancelled>());
+        assert!(watchdog.killed());
+        assert!(watchdog.run(|| Ok(())).unwrap_err().is::<Cancelled>());
+    }
+}